@@ -0,0 +1,432 @@
+// cpi.rs
+
+// Cross-program invocation helpers for programs that depend on this crate
+// with the `no-entrypoint` feature. Each helper takes `accounts` in the exact
+// order processor.rs reads them; `signer_seeds` lets the caller sign for PDAs
+// it owns, e.g. a PDA holding a seat.
+
+use crate::instruction::WagerInstruction;
+use crate::state::{Wager, Judgment};
+
+use borsh::to_vec;
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+fn invoke_wager<'a>(
+    wager_program: &AccountInfo<'a>,
+    data: &WagerInstruction,
+    accounts: &[AccountInfo<'a>],
+    metas: Vec<AccountMeta>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let encoded_data = to_vec(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let instruction = Instruction::new_with_bytes(*wager_program.key, &encoded_data, metas);
+
+    let mut account_infos = accounts.to_vec();
+    account_infos.push(wager_program.clone());
+
+    invoke_signed(&instruction, &account_infos, signer_seeds)
+}
+
+fn meta(account: &AccountInfo, is_writable: bool, is_signer: bool) -> AccountMeta {
+    if is_writable {
+        AccountMeta::new(*account.key, is_signer)
+    } else {
+        AccountMeta::new_readonly(*account.key, is_signer)
+    }
+}
+
+fn check_len(accounts: &[AccountInfo], expected: usize) -> ProgramResult {
+    if accounts.len() != expected {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    Ok(())
+}
+
+// accounts: [payer, wager, counter, vault, system_program, seat_0..seat_n]
+// token wagers append [mint, token_program]
+pub fn create_wager<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    wager: Wager,
+    reserved_seats: Vec<Pubkey>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let seat_end = 5 + reserved_seats.len();
+    let token_len = if wager.is_native() { 0 } else { 2 };
+    check_len(accounts, seat_end + token_len)?;
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], true, false),
+        meta(&accounts[3], true, false),
+        meta(&accounts[4], false, false),
+    ];
+    metas.extend(accounts[5..seat_end].iter().map(|seat| meta(seat, true, false)));
+    metas.extend(accounts[seat_end..].iter().map(|account| meta(account, false, false)));
+
+    let data = WagerInstruction::CreateWager { wager, reserved_seats };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [creator, wager, system_program, seat_0..seat_start, seat_start..seat_end,
+//            authority_start..authority_end]
+pub fn init_seats<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    start: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let batch_start = 3 + start as usize;
+    if accounts.len() < batch_start || (accounts.len() - batch_start) % 2 != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let count = (accounts.len() - batch_start) / 2;
+    let (seats, authorities) = accounts[batch_start..].split_at(count);
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], false, false),
+    ];
+    metas.extend(accounts[3..batch_start].iter().map(|seat| meta(seat, false, false)));
+    metas.extend(seats.iter().map(|seat| meta(seat, true, false)));
+    metas.extend(authorities.iter().map(|authority| meta(authority, false, false)));
+
+    let data = WagerInstruction::InitSeats { start, count: count as u8 };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [signer, wager, next_seat, system_program, seat_0..seat_n]
+pub fn join_wager<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], true, false),
+        meta(&accounts[3], false, false),
+    ];
+    metas.extend(accounts[4..].iter().map(|seat| meta(seat, false, false)));
+
+    invoke_wager(wager_program, &WagerInstruction::JoinWager, accounts, metas, signer_seeds)
+}
+
+// accounts: [authority, wager, payer, seat_0..seat_n]
+pub fn leave_seat<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    index: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 3 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], false, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], true, false),
+    ];
+    metas.extend(accounts[3..].iter().map(|seat| meta(seat, true, false)));
+
+    let data = WagerInstruction::LeaveSeat { index };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [authority, wager, vault, seat, system_program]
+//       or: [authority, wager, vault, seat, token_program, source, mint] for token wagers
+pub fn deposit<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    index: u8,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() != 5 && accounts.len() != 7 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], true, false),
+        meta(&accounts[3], true, false),
+        meta(&accounts[4], false, false),
+    ];
+    metas.extend(accounts.get(5).map(|source| meta(source, true, false)));
+    metas.extend(accounts.get(6).map(|mint| meta(mint, false, false)));
+
+    let data = WagerInstruction::ProcessDeposit { index, amount };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [authority, wager, seat_0..seat_n]
+pub fn update_belief<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    index: u8,
+    belief: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], false, true),
+        meta(&accounts[1], true, false),
+    ];
+    metas.extend(accounts[2..].iter().map(|seat| meta(seat, true, false)));
+
+    let data = WagerInstruction::UpdateBelief { index, belief };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [authority, wager, seat_0..seat_n]
+pub fn lock<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    index: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], false, true),
+        meta(&accounts[1], true, false),
+    ];
+    metas.extend(
+        accounts[2..]
+            .iter()
+            .enumerate()
+            .map(|(seat_index, seat)| meta(seat, seat_index == index as usize, false)),
+    );
+
+    let data = WagerInstruction::LockStatus { index };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [authority, wager, seat_0..seat_n]
+pub fn set_judgment<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    index: u8,
+    judgment: Judgment,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 2 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], false, true),
+        meta(&accounts[1], true, false),
+    ];
+    metas.extend(
+        accounts[2..]
+            .iter()
+            .enumerate()
+            .map(|(seat_index, seat)| meta(seat, seat_index == index as usize, false)),
+    );
+
+    let data = WagerInstruction::SetJudgment { index, judgment };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [arbiter, wager]
+pub fn arbiter_judgment<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    judgment: Judgment,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_len(accounts, 2)?;
+
+    let metas = vec![
+        meta(&accounts[0], false, true),
+        meta(&accounts[1], true, false),
+    ];
+
+    let data = WagerInstruction::ArbiterJudgment { judgment };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [signer, wager, vault, system_program, seat_k..seat_m, authority_k..authority_m]
+// for a batch of seats from the wager's cursor k; token wagers pass the token
+// program and each authority's token account instead, followed by the mint
+pub fn render_payouts<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // An odd count means a trailing token mint
+    let seat_count = (accounts.len() - 4) / 2;
+    let (seats, rest) = accounts[4..].split_at(seat_count);
+    let (authorities, mint) = rest.split_at(seat_count);
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], true, false),
+        meta(&accounts[3], false, false),
+    ];
+    metas.extend(seats.iter().map(|seat| meta(seat, false, false)));
+    metas.extend(authorities.iter().map(|authority| meta(authority, true, false)));
+    metas.extend(mint.iter().map(|mint| meta(mint, false, false)));
+
+    invoke_wager(wager_program, &WagerInstruction::RenderPayouts, accounts, metas, signer_seeds)
+}
+
+// accounts: [signer, wager, vault, system_program, seat_k..seat_m, authority_k..authority_m]
+// for a batch of seats from the wager's cursor k; token wagers pass the token
+// program and each authority's token account instead, followed by the mint
+pub fn cancel_wager<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    // An odd count means a trailing token mint
+    let seat_count = (accounts.len() - 4) / 2;
+    let (seats, rest) = accounts[4..].split_at(seat_count);
+    let (authorities, mint) = rest.split_at(seat_count);
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], true, false),
+        meta(&accounts[3], false, false),
+    ];
+    metas.extend(seats.iter().map(|seat| meta(seat, false, false)));
+    metas.extend(authorities.iter().map(|authority| meta(authority, true, false)));
+    metas.extend(mint.iter().map(|mint| meta(mint, false, false)));
+
+    invoke_wager(wager_program, &WagerInstruction::CancelWager, accounts, metas, signer_seeds)
+}
+
+// accounts: [signer, wager, vault, creator, system_program | token_program, seat_k..seat_n,
+//            rent recipient of each of those seats]
+// for the wager's last seats k..n; the batch from seat 0 closes the wager too
+// token wagers append the mint, writable so withheld transfer fees can be harvested,
+// and a token account of the creator that takes any tokens left in the vault
+pub fn close_wager<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() < 5 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], true, false),
+        meta(&accounts[3], true, false),
+        meta(&accounts[4], false, false),
+    ];
+    metas.extend(accounts[5..].iter().map(|seat| meta(seat, true, false)));
+
+    invoke_wager(wager_program, &WagerInstruction::CloseWager, accounts, metas, signer_seeds)
+}
+
+// accounts: [oracle, attestation, system_program]
+pub fn publish_attestation<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    contract: Pubkey,
+    outcome: Judgment,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_len(accounts, 3)?;
+
+    let metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], false, false),
+    ];
+
+    let data = WagerInstruction::PublishAttestation { contract, outcome };
+    invoke_wager(wager_program, &data, accounts, metas, signer_seeds)
+}
+
+// accounts: [signer, wager, attestation]
+pub fn resolve_from_oracle<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    check_len(accounts, 3)?;
+
+    let metas = vec![
+        meta(&accounts[0], false, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], false, false),
+    ];
+
+    invoke_wager(wager_program, &WagerInstruction::ResolveFromOracle, accounts, metas, signer_seeds)
+}
+
+// accounts: [signer, wager], plus the attestation for oracle fallbacks
+pub fn escalate_dispute<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() != 2 && accounts.len() != 3 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], false, true),
+        meta(&accounts[1], true, false),
+    ];
+    metas.extend(accounts[2..].iter().map(|attestation| meta(attestation, false, false)));
+
+    invoke_wager(wager_program, &WagerInstruction::EscalateDispute, accounts, metas, signer_seeds)
+}
+
+// accounts: [payer, account, system_program]; wagers append their seat_0
+pub fn migrate_account<'a>(
+    wager_program: &AccountInfo<'a>,
+    accounts: &[AccountInfo<'a>],
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if accounts.len() != 3 && accounts.len() != 4 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let mut metas = vec![
+        meta(&accounts[0], true, true),
+        meta(&accounts[1], true, false),
+        meta(&accounts[2], false, false),
+    ];
+    metas.extend(accounts[3..].iter().map(|seat| meta(seat, false, false)));
+
+    invoke_wager(wager_program, &WagerInstruction::MigrateAccount, accounts, metas, signer_seeds)
+}
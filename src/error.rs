@@ -0,0 +1,178 @@
+// error.rs
+
+use std::fmt;
+
+use solana_program::{
+    instruction::InstructionError,
+    program_error::ProgramError,
+};
+
+// Program-specific failures, surfaced as ProgramError::Custom(code)
+// Codes are part of the public interface: append new variants, never renumber
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WagerError {
+    WrongSeatAuthority = 0,
+    InvalidVault = 1,
+    InvalidSeat = 2,
+    InvalidBelief = 3,
+    SeatNotOpen = 4,
+    SeatNotStaked = 5,
+    BeliefUnset = 6,
+    WagerNotLocked = 7,
+    JudgmentsDisagree = 8,
+    WagerNotResolved = 9,
+    AlreadyResolved = 10,
+    AlreadySettled = 11,
+    StakeMismatch = 12,
+    SeatCountMismatch = 13,
+    // Reserved: outcomes are scored for any seat count now, so nothing returns
+    // it, but the code stays taken so later ones keep their numbers
+    UnsupportedSeatCount = 14,
+    InvalidAccountType = 15,
+    UnsupportedAccountVersion = 16,
+    NotLegacyAccount = 17,
+    InvalidPhase = 18,
+    InvalidDeadlines = 19,
+    DeadlinePassed = 20,
+    CancelNotAllowed = 21,
+    NotWagerCreator = 22,
+    InvalidMint = 23,
+    OracleNotConfigured = 24,
+    InvalidAttestation = 25,
+    ArbiterNotConfigured = 26,
+    NotWagerArbiter = 27,
+    ArbiterCannotHoldSeat = 28,
+    ArbitrationNotAllowed = 29,
+    InvalidFallback = 30,
+    DisputeOpen = 31,
+    WagerFull = 32,
+    TooManyReservedSeats = 33,
+    DuplicateSeatAuthority = 34,
+    ZeroStake = 35,
+    InvalidWagerAddress = 36,
+    SeatsNotInitialized = 37,
+    WrongRentRecipient = 38,
+    PayoutsPending = 39,
+}
+
+impl WagerError {
+    pub fn from_code(code: u32) -> Option<Self> {
+        let error = match code {
+            0 => Self::WrongSeatAuthority,
+            1 => Self::InvalidVault,
+            2 => Self::InvalidSeat,
+            3 => Self::InvalidBelief,
+            4 => Self::SeatNotOpen,
+            5 => Self::SeatNotStaked,
+            6 => Self::BeliefUnset,
+            7 => Self::WagerNotLocked,
+            8 => Self::JudgmentsDisagree,
+            9 => Self::WagerNotResolved,
+            10 => Self::AlreadyResolved,
+            11 => Self::AlreadySettled,
+            12 => Self::StakeMismatch,
+            13 => Self::SeatCountMismatch,
+            14 => Self::UnsupportedSeatCount,
+            15 => Self::InvalidAccountType,
+            16 => Self::UnsupportedAccountVersion,
+            17 => Self::NotLegacyAccount,
+            18 => Self::InvalidPhase,
+            19 => Self::InvalidDeadlines,
+            20 => Self::DeadlinePassed,
+            21 => Self::CancelNotAllowed,
+            22 => Self::NotWagerCreator,
+            23 => Self::InvalidMint,
+            24 => Self::OracleNotConfigured,
+            25 => Self::InvalidAttestation,
+            26 => Self::ArbiterNotConfigured,
+            27 => Self::NotWagerArbiter,
+            28 => Self::ArbiterCannotHoldSeat,
+            29 => Self::ArbitrationNotAllowed,
+            30 => Self::InvalidFallback,
+            31 => Self::DisputeOpen,
+            32 => Self::WagerFull,
+            33 => Self::TooManyReservedSeats,
+            34 => Self::DuplicateSeatAuthority,
+            35 => Self::ZeroStake,
+            36 => Self::InvalidWagerAddress,
+            37 => Self::SeatsNotInitialized,
+            38 => Self::WrongRentRecipient,
+            39 => Self::PayoutsPending,
+            _ => return None,
+        };
+
+        Some(error)
+    }
+
+    // Client-side decoding of errors returned by the program
+    pub fn from_program_error(error: &ProgramError) -> Option<Self> {
+        match error {
+            ProgramError::Custom(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+
+    pub fn from_instruction_error(error: &InstructionError) -> Option<Self> {
+        match error {
+            InstructionError::Custom(code) => Self::from_code(*code),
+            _ => None,
+        }
+    }
+}
+
+impl From<WagerError> for ProgramError {
+    fn from(error: WagerError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
+impl fmt::Display for WagerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::WrongSeatAuthority => "signer does not hold this seat",
+            Self::InvalidVault => "vault is not the PDA for this wager",
+            Self::InvalidSeat => "seat is not a PDA of this wager",
+            Self::InvalidBelief => "belief must be between 0 and 100",
+            Self::SeatNotOpen => "seat has already been staked",
+            Self::SeatNotStaked => "seat must be staked",
+            Self::BeliefUnset => "seat has not recorded a belief",
+            Self::WagerNotLocked => "every seat must be locked",
+            Self::JudgmentsDisagree => "seat judgments disagree",
+            Self::WagerNotResolved => "wager has no agreed outcome yet",
+            Self::AlreadyResolved => "wager outcome is already decided",
+            Self::AlreadySettled => "wager has already been settled",
+            Self::StakeMismatch => "amount does not match the wager stake",
+            Self::SeatCountMismatch => "every seat of the wager must be passed",
+            Self::UnsupportedSeatCount => "reserved error code, no longer returned",
+            Self::InvalidAccountType => "account is not of the expected type",
+            Self::UnsupportedAccountVersion => "account layout version is not supported",
+            Self::NotLegacyAccount => "account is not a v0 wager or seat",
+            Self::InvalidPhase => "action is not allowed in the wager's current phase",
+            Self::InvalidDeadlines => "deadlines must be in the future and ordered join, lock, resolve",
+            Self::DeadlinePassed => "the window for this action has closed",
+            Self::CancelNotAllowed => "only the creator, or a seat holder before any lock, can cancel",
+            Self::NotWagerCreator => "account is not the creator recorded on the wager",
+            Self::InvalidMint => "token account or mint does not match the wager mint",
+            Self::OracleNotConfigured => "wager does not name an oracle",
+            Self::InvalidAttestation => "attestation was not published by the wager's oracle for its contract",
+            Self::ArbiterNotConfigured => "wager does not name an arbiter",
+            Self::NotWagerArbiter => "signer is not the arbiter recorded on the wager",
+            Self::ArbiterCannotHoldSeat => "the arbiter cannot hold a seat in the wager it judges",
+            Self::ArbitrationNotAllowed => "arbiter may only rule on escalated disputes or overdue judgments",
+            Self::InvalidFallback => "dispute fallback is not configured on the wager or is not applied this way",
+            Self::DisputeOpen => "seats can still amend their judgments",
+            Self::WagerFull => "every seat up to the wager's capacity is taken",
+            Self::TooManyReservedSeats => "more seats are reserved than the wager has capacity for",
+            Self::DuplicateSeatAuthority => "an authority can only hold one seat",
+            Self::ZeroStake => "wager stake must be greater than zero",
+            Self::InvalidWagerAddress => "wager is not the creator's next wager address",
+            Self::SeatsNotInitialized => "every reserved seat must be created first",
+            Self::WrongRentRecipient => "seat rent must go back to whoever paid for the seat",
+            Self::PayoutsPending => "every seat has to be paid out before the wager closes",
+        };
+
+        f.write_str(reason)
+    }
+}
+
+impl std::error::Error for WagerError {}
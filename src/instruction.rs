@@ -7,17 +7,28 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey,
-    msg,
 };
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+// Decoded once per call, so boxing the wager would only add an allocation
+#[allow(clippy::large_enum_variant)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
 pub enum WagerInstruction {
     CreateWager { wager: Wager, reserved_seats: Vec<Pubkey> },
-    //ProcessDeposit { amount: u64 },
-    //UpdateBelief { belief: u8 },
-    //LockStatus,
-    //SetJudgment { judgment: Judgment },
-    //RenderPayouts,
+    ProcessDeposit { index: u8, amount: u64 },
+    UpdateBelief { index: u8, belief: u8 },
+    LockStatus { index: u8 },
+    SetJudgment { index: u8, judgment: Judgment },
+    RenderPayouts,
+    MigrateAccount,
+    CancelWager,
+    CloseWager,
+    PublishAttestation { contract: Pubkey, outcome: Judgment },
+    ResolveFromOracle,
+    ArbiterJudgment { judgment: Judgment },
+    EscalateDispute,
+    JoinWager,
+    InitSeats { start: u8, count: u8 },
+    LeaveSeat { index: u8 },
 }
 
 impl WagerInstruction {
@@ -28,60 +39,125 @@ impl WagerInstruction {
             .split_first()
             .ok_or(ProgramError::InvalidInstructionData)?;
 
-        msg!("input {:?}", input);
- 
         // Match instruction type and parse remaining bytes based on variant
         match variant {
             0 => {
-                const WAGER_SIZE: usize = Wager::SPACE;
+                let mut data = rest;
 
-                let wager = Wager::try_from_slice(&rest[..WAGER_SIZE])
+                let wager = Wager::deserialize(&mut data)
                     .map_err(|_| ProgramError::InvalidInstructionData)?;
-        
-                let reserved_seats = Vec::<Pubkey>::try_from_slice(&rest[WAGER_SIZE..])
+
+                // Check the declared length before reading any seats
+                let seat_len = u32::deserialize(&mut data)
                     .map_err(|_| ProgramError::InvalidInstructionData)?;
 
+                if seat_len > wager.capacity as u32 {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+
+                let mut reserved_seats = Vec::with_capacity(seat_len as usize);
+                for _ in 0..seat_len {
+                    let seat_authority = Pubkey::deserialize(&mut data)
+                        .map_err(|_| ProgramError::InvalidInstructionData)?;
+                    reserved_seats.push(seat_authority);
+                }
+
+                // Reject trailing bytes
+                if !data.is_empty() {
+                    return Err(ProgramError::InvalidInstructionData);
+                }
+
                 Ok(Self::CreateWager { wager, reserved_seats, })
             }
-            /* 
             1 => {
-                let amount = u64::try_from_slice(rest)
+                let (index, amount) = <(u8, u64)>::try_from_slice(rest)
                     .map_err(|_| ProgramError::InvalidInstructionData)?;
                 
-                Ok(Self::ProcessDeposit { amount })
+                Ok(Self::ProcessDeposit { index, amount })
             }
             2 => {
-                let belief = u8::try_from_slice(rest)
+                let (index, belief) = <(u8, u8)>::try_from_slice(rest)
                     .map_err(|_| ProgramError::InvalidInstructionData)?;
                 
-                Ok(Self::UpdateBelief { belief })
+                Ok(Self::UpdateBelief { index, belief })
             }
             3 => {
-                Ok(Self::LockStatus)
+                let index = u8::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Ok(Self::LockStatus { index })
             }
             4 => {
-                let (&decision_byte, _) = rest
-                    .split_first()
-                    .ok_or(ProgramError::InvalidInstructionData)?;
+                let (index, decision_byte) = <(u8, u8)>::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
 
-                let judgment = match decision_byte {
-                    0 => Judgment::Pending,
-                    1 => Judgment::Landed,
-                    2 => Judgment::Missed,
-                    3 => Judgment::Push,
-                    _ => return Err(ProgramError::InvalidInstructionData),
-                };
+                let judgment = unpack_judgment(decision_byte)?;
 
-                Ok(Self::SetJudgment { judgment })
+                Ok(Self::SetJudgment { index, judgment })
             }
-            5 => {
+            5 if rest.is_empty() => {
                 Ok(Self::RenderPayouts)
             }
-            */
+            6 if rest.is_empty() => {
+                Ok(Self::MigrateAccount)
+            }
+            7 if rest.is_empty() => {
+                Ok(Self::CancelWager)
+            }
+            8 if rest.is_empty() => {
+                Ok(Self::CloseWager)
+            }
+            9 => {
+                let (contract, decision_byte) = <(Pubkey, u8)>::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                let outcome = unpack_judgment(decision_byte)?;
+
+                Ok(Self::PublishAttestation { contract, outcome })
+            }
+            10 if rest.is_empty() => {
+                Ok(Self::ResolveFromOracle)
+            }
+            11 => {
+                let decision_byte = u8::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                let judgment = unpack_judgment(decision_byte)?;
+
+                Ok(Self::ArbiterJudgment { judgment })
+            }
+            12 if rest.is_empty() => {
+                Ok(Self::EscalateDispute)
+            }
+            13 if rest.is_empty() => {
+                Ok(Self::JoinWager)
+            }
+            14 => {
+                let (start, count) = <(u8, u8)>::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Ok(Self::InitSeats { start, count })
+            }
+            15 => {
+                let index = u8::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+                Ok(Self::LeaveSeat { index })
+            }
             _ => {
                 Err(ProgramError::InvalidInstructionData)
             }
             
         }
     }
-}
\ No newline at end of file
+}
+
+fn unpack_judgment(decision_byte: u8) -> Result<Judgment, ProgramError> {
+    match decision_byte {
+        0 => Ok(Judgment::Pending),
+        1 => Ok(Judgment::Landed),
+        2 => Ok(Judgment::Missed),
+        3 => Ok(Judgment::Push),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
@@ -1,28 +1,44 @@
 #![allow(unexpected_cfgs)]
 
+pub mod cpi;
+pub mod error;
 pub mod instruction;
 pub mod state;
 pub mod processor;
 
+#[cfg(feature = "sdk")]
+pub mod sdk;
+
 use instruction::WagerInstruction;
 use processor::{
     create_wager, 
-    //process_deposit,
-    //update_belief,
-    //lock_status,
-    //set_judgment,
-    //render_payout,
+    process_deposit,
+    update_belief,
+    lock_status,
+    set_judgment,
+    render_payout,
+    migrate_account,
+    cancel_wager,
+    close_wager,
+    publish_attestation,
+    resolve_from_oracle,
+    arbiter_judgment,
+    escalate_dispute,
+    join_wager,
+    init_seats,
+    leave_seat,
 };
 
 use solana_program::{
     account_info::{AccountInfo},
     entrypoint::ProgramResult,
     pubkey::Pubkey,
-    entrypoint,
     msg,
 };
 
-entrypoint!(process_instruction);
+// Programs that link this crate enable `no-entrypoint` to avoid symbol clashes
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -38,22 +54,50 @@ pub fn process_instruction(
         WagerInstruction::CreateWager { wager, reserved_seats } => {
             create_wager(program_id, accounts, wager, reserved_seats)
         }
-        /*
-        WagerInstruction::ProcessDeposit { amount } => {
-            process_deposit(program_id, accounts, amount)
+        WagerInstruction::ProcessDeposit { index, amount } => {
+            process_deposit(program_id, accounts, index, amount)
         }
-        WagerInstruction::UpdateBelief { belief } => {
-            update_belief(program_id, accounts, belief)
+        WagerInstruction::UpdateBelief { index, belief } => {
+            update_belief(program_id, accounts, index, belief)
         }
-        WagerInstruction::LockStatus => {
-            lock_status(program_id, accounts)
+        WagerInstruction::LockStatus { index } => {
+            lock_status(program_id, accounts, index)
         }
-        WagerInstruction::SetJudgment { judgment } => {
-            set_judgment(program_id, accounts, judgment)
+        WagerInstruction::SetJudgment { index, judgment } => {
+            set_judgment(program_id, accounts, index, judgment)
         }
         WagerInstruction::RenderPayouts => {
             render_payout(program_id, accounts)
         }
-         */
+        WagerInstruction::MigrateAccount => {
+            migrate_account(program_id, accounts)
+        }
+        WagerInstruction::CancelWager => {
+            cancel_wager(program_id, accounts)
+        }
+        WagerInstruction::CloseWager => {
+            close_wager(program_id, accounts)
+        }
+        WagerInstruction::PublishAttestation { contract, outcome } => {
+            publish_attestation(program_id, accounts, contract, outcome)
+        }
+        WagerInstruction::ResolveFromOracle => {
+            resolve_from_oracle(program_id, accounts)
+        }
+        WagerInstruction::ArbiterJudgment { judgment } => {
+            arbiter_judgment(program_id, accounts, judgment)
+        }
+        WagerInstruction::EscalateDispute => {
+            escalate_dispute(program_id, accounts)
+        }
+        WagerInstruction::JoinWager => {
+            join_wager(program_id, accounts)
+        }
+        WagerInstruction::InitSeats { start, count } => {
+            init_seats(program_id, accounts, start, count)
+        }
+        WagerInstruction::LeaveSeat { index } => {
+            leave_seat(program_id, accounts, index)
+        }
     }
 }
\ No newline at end of file
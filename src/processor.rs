@@ -1,35 +1,60 @@
 // processor.rs 
 
-use std::cmp;
-
-use borsh::{BorshSerialize, BorshDeserialize};
-
-use crate::state::{Wager, Seat, Status, Judgment};
+use borsh::{BorshSerialize, BorshDeserialize, to_vec};
+
+use crate::error::WagerError;
+use crate::state::{
+    Wager,
+    WagerV0,
+    WagerCounter,
+    Attestation,
+    Seat,
+    SeatV0,
+    Phase,
+    Fallback,
+    Status,
+    Judgment,
+    MAX_BELIEF,
+    UNSET_BELIEF,
+    all_locked,
+    all_judged,
+    consensus,
+};
 
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    program_error::ProgramError, // TODO: Use custom error instead of ProgramError::Immutable
+    program_error::ProgramError,
     pubkey::Pubkey,
     program::invoke,
     program::invoke_signed,
     sysvar::{rent::Rent, Sysvar},
     // hash::hash,
-    system_program,
-    system_instruction,
     msg,
     clock::Clock,
 };
 
+use solana_sdk_ids::system_program;
+use solana_system_interface::instruction as system_instruction;
+
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeAmount,
+    BaseStateWithExtensions,
+    ExtensionType,
+    StateWithExtensions,
+};
+use spl_token_2022::state::{Account as TokenAccount, Mint};
+
 pub fn create_wager(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    wager: Wager,
+    mut wager: Wager,
     reserved_seats: Vec<Pubkey>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let payer = next_account_info(accounts_iter)?;
     let wager_account = next_account_info(accounts_iter)?;
+    let counter_account = next_account_info(accounts_iter)?;
     let vault_account = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
 
@@ -43,22 +68,97 @@ pub fn create_wager(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if !wager_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // An existing wager is never overwritten
+    if wager_account.owner == program_id || !wager_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    if wager.stake == 0 {
+        return Err(WagerError::ZeroStake.into());
+    }
+
+    // Seats past the first batch of reserved ones are created with InitSeats
+    if reserved_seats.len() > wager.reserved_count as usize
+        || wager.reserved_count > wager.capacity
+    {
+        return Err(WagerError::TooManyReservedSeats.into());
+    }
+
+    check_seat_authorities(&wager, &reserved_seats)?;
+
+    // Reject windows that are already closed or out of order
+    let clock = Clock::get()?;
+    wager.check_deadlines(clock.unix_timestamp)?;
+
+    if wager.dispute_window < 0 {
+        return Err(WagerError::InvalidDeadlines.into());
     }
 
-    // create vault
-    let vault_related_accounts = &[
+    // Disputes can only fall back to a resolver the wager actually names
+    let has_fallback = match wager.fallback {
+        Fallback::Push => true,
+        Fallback::Arbiter => wager.has_arbiter(),
+        Fallback::Oracle => wager.has_oracle(),
+    };
+
+    if !has_fallback {
+        return Err(WagerError::InvalidFallback.into());
+    }
+
+    let counter_related_accounts = &[
         payer.clone(),
-        wager_account.clone(),
-        vault_account.clone(),
+        counter_account.clone(),
         system_program.clone(),
     ];
 
-    create_vault(
-        program_id,
-        vault_related_accounts,
-    )?;
+    let mut counter = load_wager_counter(program_id, counter_related_accounts)?;
+    let nonce = counter.wager_count.to_le_bytes();
+
+    // The wager lives at the creator's next nonce, so clients can re-derive it
+    let (wager_pda, wager_bump) = Pubkey::find_program_address(
+        &[b"wager", payer.key.as_ref(), &nonce],
+        program_id
+    );
+
+    if wager_pda != *wager_account.key {
+        return Err(WagerError::InvalidWagerAddress.into());
+    }
+
+    // create vault; token wagers pass the mint and token program after the seats
+    let vault_bump = if wager.is_native() {
+        let vault_related_accounts = &[
+            payer.clone(),
+            wager_account.clone(),
+            vault_account.clone(),
+            system_program.clone(),
+        ];
+
+        create_vault(
+            program_id,
+            vault_related_accounts,
+        )?
+    } else {
+        let mint_account = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+
+        if *mint_account.key != wager.mint {
+            return Err(WagerError::InvalidMint.into());
+        }
+
+        let vault_related_accounts = &[
+            payer.clone(),
+            wager_account.clone(),
+            vault_account.clone(),
+            system_program.clone(),
+            mint_account.clone(),
+            token_program.clone(),
+        ];
+
+        create_token_vault(
+            program_id,
+            vault_related_accounts,
+        )?
+    };
 
     // create seat
     for (index, &seat_authority) in reserved_seats.iter().enumerate() {
@@ -77,46 +177,136 @@ pub fn create_wager(
         )?;
     }
 
-    // Create wager account
-    let rent = Rent::get()?;
-    let space = Wager::SPACE;
-    let required_lamports = rent.minimum_balance(space);
+    wager.discriminator = Wager::DISCRIMINATOR;
+    wager.version = Wager::VERSION;
+    wager.phase = Phase::Created;
+    wager.creator = *payer.key;
+    wager.vault = *vault_account.key;
+    wager.vault_bump = vault_bump;
+    wager.seat_count = reserved_seats.len() as u8;
+    wager.staked_count = 0;
+    wager.locked_count = 0;
+    wager.pot = 0;
+    wager.paid = 0;
+    wager.cursor = 0;
+    wager.disputed_until = 0;
+    wager.outcome = Judgment::Pending;
+    wager.belief_squares = 0;
+    wager.doubt_squares = 0;
 
-    invoke(
-        &system_instruction::create_account(
-            payer.key,
-            wager_account.key,
-            required_lamports,
-            space as u64,
-            program_id,
-        ),
-        &[
-            payer.clone(), 
-            wager_account.clone(), 
-            system_program.clone(),
-        ],
+    // Create wager account
+    create_pda_account(
+        payer,
+        wager_account,
+        system_program,
+        Wager::SPACE,
+        program_id,
+        &[b"wager", payer.key.as_ref(), &nonce, &[wager_bump]],
     )?;
 
     wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    counter.wager_count = counter.wager_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    counter.serialize(&mut &mut counter_account.data.borrow_mut()[..])?;
     
     msg!("Wager stored successfully!");
 
     Ok(())
 }
 
-pub fn create_vault(
+// Loads the creator's wager counter, creating it on their first wager
+fn load_wager_counter(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+) -> Result<WagerCounter, ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let counter_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // An existing counter is verified against its stored bump
+    if counter_account.owner == program_id {
+        let counter = WagerCounter::load(&counter_account.data.borrow())?;
+        let counter_pda = Pubkey::create_program_address(
+            &[b"counter", payer.key.as_ref(), &[counter.bump]],
+            program_id
+        ).map_err(|_| WagerError::InvalidWagerAddress)?;
+
+        if counter_pda != *counter_account.key {
+            return Err(WagerError::InvalidWagerAddress.into());
+        }
+
+        return Ok(counter);
+    }
+
+    let (counter_pda, counter_bump) = Pubkey::find_program_address(
+        &[b"counter", payer.key.as_ref()],
+        program_id
+    );
+
+    if counter_pda != *counter_account.key {
+        return Err(WagerError::InvalidWagerAddress.into());
+    }
+
+    create_pda_account(
+        payer,
+        counter_account,
+        system_program,
+        WagerCounter::SPACE,
+        program_id,
+        &[b"counter", payer.key.as_ref(), &[counter_bump]],
+    )?;
+
+    Ok(WagerCounter::new(*payer.key, counter_bump))
+}
+
+// Creates a PDA signed for by `seeds`. Anyone can send lamports to an address
+// before it exists, which would make create_account fail, so only the rent
+// shortfall is transferred before allocating and assigning the account
+fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
 ) -> ProgramResult {
+    let rent = Rent::get()?;
+    let shortfall = rent.minimum_balance(space).saturating_sub(account.lamports());
+
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(account.key, space as u64),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )?;
+
+    invoke_signed(
+        &system_instruction::assign(account.key, owner),
+        &[account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+// Returns the vault bump so the wager can record it
+pub fn create_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<u8, ProgramError> {
     let accounts_iter = &mut accounts.iter();
     let payer = next_account_info(accounts_iter)?;
     let wager_account = next_account_info(accounts_iter)?;
     let vault_account = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
 
-    let rent = Rent::get()?;
-    let lamports = rent.minimum_balance(0); // No data, just enough for rent exemption
-
     // create vault PDA
     let (pda, bump) = Pubkey::find_program_address(
         &[b"vault", wager_account.key.as_ref()],
@@ -124,32 +314,87 @@ pub fn create_vault(
     );
 
     if pda != *vault_account.key {
-        return Err(ProgramError::InvalidArgument);
+        return Err(WagerError::InvalidVault.into());
     }
 
-    // let vault_seeds = &[b"vault", wager_key.as_ref(), &[vault_bump]];
+    // The vault holds no data, just enough lamports for rent exemption
+    create_pda_account(
+        payer,
+        vault_account,
+        system_program,
+        0,
+        &system_program::ID,
+        &[b"vault", wager_account.key.as_ref(), &[bump]],
+    )?;
 
-    invoke_signed(
-        &system_instruction::create_account(
-            payer.key,
+    msg!("Vault created successfully!");
+    Ok(bump)
+}
+
+// Token stakes sit in a token account at the vault PDA; the PDA is its own
+// authority so payouts can be signed with the vault seeds. Returns the bump
+// like create_vault
+pub fn create_token_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<u8, ProgramError> {
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let mint_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    if !is_token_program(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if mint_account.owner != token_program.key {
+        return Err(WagerError::InvalidMint.into());
+    }
+
+    // Token-2022 mints may require extensions on every account they hold
+    let space = {
+        let data = mint_account.data.borrow();
+        let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+        let required = ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+        ExtensionType::try_calculate_account_len::<TokenAccount>(&required)?
+    };
+
+    let (pda, bump) = Pubkey::find_program_address(
+        &[b"vault", wager_account.key.as_ref()],
+        program_id
+    );
+
+    if pda != *vault_account.key {
+        return Err(WagerError::InvalidVault.into());
+    }
+
+    create_pda_account(
+        payer,
+        vault_account,
+        system_program,
+        space,
+        token_program.key,
+        &[b"vault", wager_account.key.as_ref(), &[bump]],
+    )?;
+
+    invoke(
+        &spl_token_2022::instruction::initialize_account3(
+            token_program.key,
             vault_account.key,
-            lamports,
-            0, // vault holds no data
-            &system_program::ID, // formerly program_id ???
-        ),
+            mint_account.key,
+            vault_account.key,
+        )?,
         &[
-            payer.clone(), 
-            vault_account.clone(), 
-            system_program.clone()],
-        &[&[
-            b"vault", 
-            wager_account.key.as_ref(), 
-            &[bump]
-        ]],
+            vault_account.clone(),
+            mint_account.clone(),
+        ],
     )?;
 
-    msg!("Vault created successfully!");
-    Ok(())
+    msg!("Token vault created successfully!");
+    Ok(bump)
 }
 
 pub fn create_seat(
@@ -164,10 +409,6 @@ pub fn create_seat(
     let seat_account = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
 
-    let rent = Rent::get()?;
-    let space = Seat::SPACE;
-    let required_lamports = rent.minimum_balance(space);
-
     // create seat PDA
     let (pda, bump) = Pubkey::find_program_address(
         &[b"seat", wager_account.key.as_ref(), &index.to_le_bytes()],
@@ -175,32 +416,20 @@ pub fn create_seat(
     );
 
     if pda != *seat_account.key {
-        return Err(ProgramError::InvalidArgument);
+        return Err(WagerError::InvalidSeat.into());
     }
 
     let clock = Clock::get()?;
     let current_timestamp = clock.unix_timestamp;
-    let seat = Seat::take(*wager_account.key, *authority, current_timestamp);
+    let seat = Seat::take(*wager_account.key, *authority, bump, current_timestamp);
 
-    invoke_signed(
-        &system_instruction::create_account(
-            payer.key,
-            seat_account.key,
-            required_lamports,
-            space as u64,
-            program_id, // &system_program::ID,
-        ),
-        &[
-            payer.clone(),
-            seat_account.clone(),
-            system_program.clone(),
-        ],
-        &[&[
-            b"seat",
-            wager_account.key.as_ref(),
-            &index.to_le_bytes(),
-            &[bump],
-        ]],
+    create_pda_account(
+        payer,
+        seat_account,
+        system_program,
+        Seat::SPACE,
+        program_id,
+        &[b"seat", wager_account.key.as_ref(), &index.to_le_bytes(), &[bump]],
     )?;
 
     seat.serialize(&mut &mut seat_account.data.borrow_mut()[..])?;
@@ -208,210 +437,1193 @@ pub fn create_seat(
     Ok(())
 }
 
-/*
-pub fn process_deposit(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    amount: u64,
-) -> ProgramResult {
-    let accounts_iter = &mut accounts.iter();
-    let payer = next_account_info(accounts_iter)?;
-    let wager_account = next_account_info(accounts_iter)?;
-    let vault_account = next_account_info(accounts_iter)?;
-    let system_program = next_account_info(accounts_iter)?;
-    
-    // Verify accounts
-    if !payer.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+// Deserializes the seat at `index` of a wager after checking that the
+// account is the program-owned seat PDA for that wager
+fn load_seat(
+    program_id: &Pubkey,
+    wager_key: &Pubkey,
+    seat_account: &AccountInfo,
+    index: u8,
+) -> Result<Seat, ProgramError> {
+    if seat_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
     }
-    
-    if *system_program.key != system_program::ID {
-        return Err(ProgramError::InvalidArgument);
+
+    let seat = Seat::load(&seat_account.data.borrow())?;
+
+    // The stored bump saves searching for it on every instruction
+    let seat_pda = Pubkey::create_program_address(
+        &[b"seat", wager_key.as_ref(), &index.to_le_bytes(), &[seat.bump]],
+        program_id
+    ).map_err(|_| WagerError::InvalidSeat)?;
+
+    if seat_pda != *seat_account.key {
+        return Err(WagerError::InvalidSeat.into());
     }
-    
-    // Get wager data
-    let mut wager = Wager::try_from_slice(&wager_account.data.borrow())?;
-    
-    // Verify stake amount
-    if amount < wager.stake {
-        return Err(ProgramError::InsufficientFunds);
-    }
-
-    /*
-    // TODO: rework seat logic
-    if versus.seat_a.wallet == system_program::ID {
-        versus.seat_a.wallet = *payer.key;
-        msg!("Seat A claimed by: {}", payer.key);
-    } else if versus.seat_b.wallet == system_program::ID {
-        versus.seat_b.wallet = *payer.key;
-        msg!("Seat B claimed by: {}", payer.key);
-    } else {
-        msg!("Both seats already taken!");
-        return Err(ProgramError::InvalidAccountData);
+
+    if seat.wager != *wager_key {
+        return Err(WagerError::InvalidSeat.into());
     }
 
-    // Find matching seat
-    let seat = if payer.key == &versus.seat_a.wallet {
-        &mut versus.seat_a
-    } else if payer.key == &versus.seat_b.wallet {
-        &mut versus.seat_b
-    } else {
-        msg!("no matching seat");
-        return Err(ProgramError::InvalidArgument);
-    };
-     */
+    Ok(seat)
+}
 
-    // Verify wallet has not yet paid stake
-    if seat.status != Status::Open {
-        return Err(ProgramError::Immutable);
+// Deserializes every seat of a wager, which must be passed in index order
+fn load_seats(
+    program_id: &Pubkey,
+    wager_key: &Pubkey,
+    wager: &Wager,
+    seat_accounts: &[AccountInfo],
+) -> Result<Vec<Seat>, ProgramError> {
+    if seat_accounts.len() != wager.seat_count as usize {
+        return Err(WagerError::SeatCountMismatch.into());
+    }
+
+    load_seat_batch(program_id, wager_key, wager, seat_accounts, 0)
+}
+
+// Deserializes seats `start..start + seat_accounts.len()`, for instructions
+// that work through a wager's seats in batches
+fn load_seat_batch(
+    program_id: &Pubkey,
+    wager_key: &Pubkey,
+    wager: &Wager,
+    seat_accounts: &[AccountInfo],
+    start: u8,
+) -> Result<Vec<Seat>, ProgramError> {
+    if start as usize + seat_accounts.len() > wager.seat_count as usize {
+        return Err(WagerError::SeatCountMismatch.into());
+    }
+
+    seat_accounts
+        .iter()
+        .enumerate()
+        .map(|(offset, seat_account)| {
+            load_seat(program_id, wager_key, seat_account, start + offset as u8)
+        })
+        .collect()
+}
+
+// Reserved seats are unique and never held by the arbiter
+fn check_seat_authorities(wager: &Wager, authorities: &[Pubkey]) -> ProgramResult {
+    for (index, authority) in authorities.iter().enumerate() {
+        if authorities[..index].contains(authority) {
+            return Err(WagerError::DuplicateSeatAuthority.into());
+        }
+
+        // The arbiter has to stay impartial
+        if wager.has_arbiter() && wager.arbiter == *authority {
+            return Err(WagerError::ArbiterCannotHoldSeat.into());
+        }
     }
-    
-    // Transfer funds from user to program account
-    invoke(
-        &system_instruction::transfer(
-            payer.key,
-            vault_account.key,
-            amount,
-        ),
-        &[
-            payer.clone(),
-            vault_account.clone(),
-            system_program.clone(),
-        ],
-    )?;
 
-    seat.status = Status::Staked;
-    
-    // Serialize and save updated state
-    versus.serialize(&mut *wager_account.data.borrow_mut())?;
-    
     Ok(())
 }
 
-pub fn update_belief(
+// Creates reserved seats `start..start + count` for the authorities passed
+// after the seat PDAs, so wagers with more reserved seats than fit in one
+// transaction can be set up in batches. The seats created so far come first,
+// so no authority is seated twice across batches
+pub fn init_seats(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    belief: u8,
+    start: u8,
+    count: u8,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-    let signer = next_account_info(accounts_iter)?;
+    let creator = next_account_info(accounts_iter)?;
     let wager_account = next_account_info(accounts_iter)?;
-    
-    // Verify account ownership
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Existing seats, then the new seats and one authority for each
+    let remaining_accounts = accounts_iter.as_slice();
+    if remaining_accounts.len() != start as usize + 2 * count as usize {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let (existing_accounts, remaining_accounts) = remaining_accounts.split_at(start as usize);
+    let (seat_accounts, authority_accounts) = remaining_accounts.split_at(count as usize);
+
+    // Verify accounts
+    if !creator.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
     if wager_account.owner != program_id {
         return Err(ProgramError::IncorrectProgramId);
     }
-    
-    // Verify signer
-    if !signer.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+    wager.expect_phase(&[Phase::Created])?;
+
+    // Only the creator decides who holds the reserved seats
+    if wager.creator != *creator.key {
+        return Err(WagerError::NotWagerCreator.into());
     }
-    
-    // Deserialize account data
-    let mut versus = Wager::try_from_slice(&wager_account.data.borrow())?;
 
-    // Find matching seat
-    let seat = if signer.key == &versus.seat_a.wallet {
-        &mut versus.seat_a
-    } else if signer.key == &versus.seat_b.wallet {
-        &mut versus.seat_b
-    } else {
-        return Err(ProgramError::InvalidArgument);
-    };
+    // Batches go in order, so `start` must be the next seat to create
+    let end = start as usize + count as usize;
+    if start != wager.seat_count {
+        return Err(WagerError::InvalidSeat.into());
+    }
 
-    // Verify wallet has paid stake before setting belief
-    if seat.status != Status::Staked {
-        return Err(ProgramError::Immutable);
-    } else {
-        seat.belief = belief;
+    if end > wager.reserved_count as usize {
+        return Err(WagerError::TooManyReservedSeats.into());
     }
 
-    // Serialize updated data back to account
-    versus.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+    let existing = load_seats(program_id, wager_account.key, &wager, existing_accounts)?;
 
-    msg!("Belief Updated!");
+    let mut authorities: Vec<Pubkey> = existing.iter().map(|seat| seat.authority).collect();
+    authorities.extend(authority_accounts.iter().map(|authority| *authority.key));
+
+    check_seat_authorities(&wager, &authorities)?;
+
+    for (offset, authority) in authorities[start as usize..].iter().enumerate() {
+        let seat_related_accounts = &[
+            creator.clone(),
+            wager_account.clone(),
+            seat_accounts[offset].clone(),
+            system_program.clone(),
+        ];
+
+        create_seat(
+            program_id,
+            seat_related_accounts,
+            authority,
+            start + offset as u8,
+        )?;
+    }
 
+    wager.seat_count = end as u8;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    msg!("Seats {}..{} created", start, end);
     Ok(())
 }
 
-pub fn lock_status(
+// Takes the next open seat for the signer, who pays its rent; seats beyond
+// the reserved ones are first come, first served up to the capacity
+pub fn join_wager(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let signer = next_account_info(accounts_iter)?;
     let wager_account = next_account_info(accounts_iter)?;
+    let seat_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let existing_accounts = accounts_iter.as_slice();
+
+    // Verify accounts
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
 
-    // Verify account ownership
     if wager_account.owner != program_id {
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    // Verify signer
-    if !signer.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
 
-    // Deserialize account data
-    let mut versus = Wager::try_from_slice(&wager_account.data.borrow())?;
+    // Seats can only be taken while stakes are still accepted
+    wager.expect_phase(&[Phase::Created, Phase::Funding])?;
 
-    // Find matching seat
-    let seat = if signer.key == &versus.seat_a.wallet {
-        &mut versus.seat_a
-    } else if signer.key == &versus.seat_b.wallet {
-        &mut versus.seat_b
+    if !wager.seats_initialized() {
+        return Err(WagerError::SeatsNotInitialized.into());
+    }
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > wager.join_deadline {
+        return Err(WagerError::DeadlinePassed.into());
+    }
+
+    // The arbiter has to stay impartial
+    if wager.has_arbiter() && wager.arbiter == *signer.key {
+        return Err(WagerError::ArbiterCannotHoldSeat.into());
+    }
+
+    if wager.seat_count >= wager.capacity {
+        return Err(WagerError::WagerFull.into());
+    }
+
+    // The seats taken so far are passed so nobody holds two
+    let seats = load_seats(program_id, wager_account.key, &wager, existing_accounts)?;
+    if seats.iter().any(|seat| seat.authority == *signer.key) {
+        return Err(WagerError::DuplicateSeatAuthority.into());
+    }
+
+    let seat_related_accounts = &[
+        signer.clone(),
+        wager_account.clone(),
+        seat_account.clone(),
+        system_program.clone(),
+    ];
+
+    create_seat(
+        program_id,
+        seat_related_accounts,
+        signer.key,
+        wager.seat_count,
+    )?;
+
+    wager.seat_count += 1;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    msg!("Seat {} joined", wager.seat_count - 1);
+    Ok(())
+}
+
+// Gives up a seat that was never staked. Later seats move down one index so
+// seats stay contiguous, and the last seat PDA is closed with its rent going
+// back to whoever paid for the leaving seat. Once the join deadline has passed
+// anyone can drop a seat that was never staked, since it could otherwise keep
+// the wager from ever locking
+pub fn leave_seat(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let payer = next_account_info(accounts_iter)?;
+    let seat_accounts = accounts_iter.as_slice();
+
+    // Verify accounts
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // Seats can only be given up while stakes are still accepted
+    wager.expect_phase(&[Phase::Created, Phase::Funding])?;
+
+    let mut seats = load_seats(program_id, wager_account.key, &wager, seat_accounts)?;
+    let bumps: Vec<u8> = seats.iter().map(|seat| seat.bump).collect();
+
+    let seat = seats
+        .get(index as usize)
+        .ok_or(WagerError::InvalidSeat)?;
+
+    // Verify signer holds this seat, unless it can no longer be staked
+    let clock = Clock::get()?;
+    if seat.authority != *signer.key && clock.unix_timestamp <= wager.join_deadline {
+        return Err(WagerError::WrongSeatAuthority.into());
+    }
+
+    // Staked seats leave through CancelWager so their stake is refunded
+    if seat.status != Status::Open {
+        return Err(WagerError::SeatNotOpen.into());
+    }
+
+    // Reserved seats were paid for by the creator, joined seats by their holder
+    let reserved = index < wager.reserved_count;
+    let rent_payer = if reserved {
+        wager.creator
+    } else {
+        seat.authority
+    };
+
+    if *payer.key != rent_payer {
+        return Err(WagerError::WrongRentRecipient.into());
+    }
+
+    seats.remove(index as usize);
+
+    // The counterparties have changed, so nobody stays locked into the old ones
+    for seat in seats.iter_mut() {
+        if seat.status == Status::Locked {
+            msg!("Lock reset for {}", seat.authority);
+            seat.status = Status::Staked;
+        }
+    }
+
+    // Seats keep the bump of the PDA they now live at
+    for ((seat, bump), seat_account) in seats.iter_mut().zip(bumps).zip(seat_accounts) {
+        seat.bump = bump;
+        seat.serialize(&mut &mut seat_account.data.borrow_mut()[..])?;
+    }
+
+    let last_seat = &seat_accounts[seats.len()];
+    close_account(last_seat, payer)?;
+
+    // A reserved slot that is given up opens for joining
+    if reserved {
+        wager.reserved_count -= 1;
+    }
+    wager.seat_count -= 1;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    msg!("Seat {} left", index);
+    Ok(())
+}
+
+pub fn process_deposit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u8,
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let seat_account = next_account_info(accounts_iter)?;
+    let transfer_program = next_account_info(accounts_iter)?;
+
+    // Verify accounts
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Get wager data
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // Stakes are only taken before every seat has locked, and only once
+    // every reserved seat exists
+    wager.expect_phase(&[Phase::Created, Phase::Funding])?;
+
+    if !wager.seats_initialized() {
+        return Err(WagerError::SeatsNotInitialized.into());
+    }
+
+    check_transfer_program(&wager, transfer_program)?;
+
+    // Seats can only be funded until the join deadline
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > wager.join_deadline {
+        return Err(WagerError::DeadlinePassed.into());
+    }
+
+    // Verify vault PDA
+    let vault_pda = Pubkey::create_program_address(
+        &[b"vault", wager_account.key.as_ref(), &[wager.vault_bump]],
+        program_id
+    ).map_err(|_| WagerError::InvalidVault)?;
+
+    if vault_pda != *vault_account.key {
+        return Err(WagerError::InvalidVault.into());
+    }
+
+    let mut seat = load_seat(program_id, wager_account.key, seat_account, index)?;
+
+    // Verify signer holds this seat
+    if seat.authority != *signer.key {
+        return Err(WagerError::WrongSeatAuthority.into());
+    }
+
+    // Verify wallet has not yet paid stake
+    if seat.status != Status::Open {
+        return Err(WagerError::SeatNotOpen.into());
+    }
+
+    // Verify stake amount
+    if amount != wager.stake {
+        return Err(WagerError::StakeMismatch.into());
+    }
+
+    // Transfer funds from user to vault
+    let received = if wager.is_native() {
+        invoke(
+            &system_instruction::transfer(
+                signer.key,
+                vault_account.key,
+                wager.stake,
+            ),
+            &[
+                signer.clone(),
+                vault_account.clone(),
+                transfer_program.clone(),
+            ],
+        )?;
+
+        wager.stake
+    } else {
+        // Token stakes come from a token account the signer controls
+        let source_account = next_account_info(accounts_iter)?;
+        let mint_account = next_account_info(accounts_iter)?;
+        let decimals = load_mint(&wager, mint_account, transfer_program)?;
+
+        let balance = token_balance(vault_account)?;
+
+        invoke(
+            &spl_token_2022::instruction::transfer_checked(
+                transfer_program.key,
+                source_account.key,
+                mint_account.key,
+                vault_account.key,
+                signer.key,
+                &[],
+                wager.stake,
+                decimals,
+            )?,
+            &[
+                source_account.clone(),
+                mint_account.clone(),
+                vault_account.clone(),
+                signer.clone(),
+                transfer_program.clone(),
+            ],
+        )?;
+
+        // Transfer fees are withheld from what arrives, so record the vault's
+        // actual gain rather than the stake that was sent
+        token_balance(vault_account)?
+            .checked_sub(balance)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+    };
+
+    wager.pot = wager
+        .pot
+        .checked_add(received)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    wager.staked_count = wager
+        .staked_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    seat.status = Status::Staked;
+    seat.last_change_at = clock.unix_timestamp;
+
+    wager.advance(Phase::Funding)?;
+
+    // Serialize and save updated state
+    seat.serialize(&mut &mut seat_account.data.borrow_mut()[..])?;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    msg!("Deposit received: {}", received);
+
+    Ok(())
+}
+
+pub fn update_belief(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u8,
+    belief: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let seat_accounts = accounts_iter.as_slice();
+
+    // Verify account ownership
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signer
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Beliefs are percentages; 255 is reserved for unset seats
+    if belief > MAX_BELIEF {
+        return Err(WagerError::InvalidBelief.into());
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // Terms are frozen once every seat has locked
+    wager.expect_phase(&[Phase::Funding])?;
+
+    // Beliefs can only move until the lock deadline
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > wager.lock_deadline {
+        return Err(WagerError::DeadlinePassed.into());
+    }
+
+    let mut seats = load_seats(program_id, wager_account.key, &wager, seat_accounts)?;
+
+    let seat = seats
+        .get_mut(index as usize)
+        .ok_or(WagerError::InvalidSeat)?;
+
+    // Verify signer holds this seat
+    if seat.authority != *signer.key {
+        return Err(WagerError::WrongSeatAuthority.into());
+    }
+
+    // Verify wallet has paid stake before setting belief
+    if seat.status != Status::Staked {
+        return Err(WagerError::SeatNotStaked.into());
+    }
+
+    seat.belief = belief;
+    seat.last_change_at = clock.unix_timestamp;
+
+    // Terms have moved, so nobody stays locked into the old ones
+    for seat in seats.iter_mut() {
+        if seat.status == Status::Locked {
+            msg!("Lock reset for {}", seat.authority);
+            seat.status = Status::Staked;
+        }
+    }
+    wager.locked_count = 0;
+
+    // Serialize updated data back to accounts
+    for (seat, seat_account) in seats.iter().zip(seat_accounts) {
+        seat.serialize(&mut &mut seat_account.data.borrow_mut()[..])?;
+    }
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    msg!("Belief Updated!");
+
+    Ok(())
+}
+
+pub fn lock_status(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let seat_accounts = accounts_iter.as_slice();
+
+    // Verify account ownership
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signer
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+    wager.expect_phase(&[Phase::Funding])?;
+
+    // Seats can only lock in until the lock deadline
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > wager.lock_deadline {
+        return Err(WagerError::DeadlinePassed.into());
+    }
+
+    let mut seats = load_seats(program_id, wager_account.key, &wager, seat_accounts)?;
+
+    let seat = seats
+        .get_mut(index as usize)
+        .ok_or(WagerError::InvalidSeat)?;
+
+    // Verify signer holds this seat
+    if seat.authority != *signer.key {
+        return Err(WagerError::WrongSeatAuthority.into());
+    }
+
+    // Only staked seats with a recorded belief can agree to terms
+    if seat.status != Status::Staked {
+        return Err(WagerError::SeatNotStaked.into());
+    }
+
+    if seat.belief == UNSET_BELIEF {
+        return Err(WagerError::BeliefUnset.into());
+    }
+
+    seat.status = Status::Locked;
+    seat.last_change_at = clock.unix_timestamp;
+
+    // Serialize updated data back to account
+    let seat_account = &seat_accounts[index as usize];
+    seat.serialize(&mut &mut seat_account.data.borrow_mut()[..])?;
+
+    msg!("Seat Locked!");
+
+    // Seat holders can only cancel while nobody is locked in
+    wager.locked_count = seats
+        .iter()
+        .filter(|seat| seat.status == Status::Locked)
+        .count() as u8;
+
+    // The last seat to lock freezes the terms for the whole wager, and with
+    // them the totals every seat's payout is scored against
+    if all_locked(&seats) {
+        msg!("Wager locked!");
+        wager.belief_squares = seats.iter().map(|seat| belief_score(Judgment::Missed, seat.belief)).sum();
+        wager.doubt_squares = seats.iter().map(|seat| belief_score(Judgment::Landed, seat.belief)).sum();
+        wager.advance(Phase::Locked)?;
+    }
+
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+pub fn set_judgment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    index: u8,
+    judgment: Judgment,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let seat_accounts = accounts_iter.as_slice();
+
+    // Verify account ownership
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signer
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // A judgment cannot be taken back, or a losing seat could stall consensus
+    if judgment == Judgment::Pending {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // A resolved wager cannot be re-judged
+    if wager.outcome != Judgment::Pending {
+        return Err(WagerError::AlreadyResolved.into());
+    }
+
+    // Judgments are only meaningful once every seat agreed to the terms
+    if wager.phase == Phase::Created || wager.phase == Phase::Funding {
+        return Err(WagerError::WagerNotLocked.into());
+    }
+
+    wager.expect_phase(&[Phase::Locked, Phase::Judging, Phase::Disputed])?;
+
+    // Judgments are accepted until the resolve deadline, amendments to a
+    // disputed wager until its dispute window closes
+    let deadline = if wager.phase == Phase::Disputed {
+        wager.disputed_until
+    } else {
+        wager.resolve_deadline
+    };
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp > deadline {
+        return Err(WagerError::DeadlinePassed.into());
+    }
+
+    let mut seats = load_seats(program_id, wager_account.key, &wager, seat_accounts)?;
+
+    let seat = seats
+        .get_mut(index as usize)
+        .ok_or(WagerError::InvalidSeat)?;
+
+    // Verify signer holds this seat
+    if seat.authority != *signer.key {
+        return Err(WagerError::WrongSeatAuthority.into());
+    }
+
+    seat.judgment = judgment;
+    seat.last_change_at = clock.unix_timestamp;
+
+    // Serialize updated data back to account
+    let seat_account = &seat_accounts[index as usize];
+    seat.serialize(&mut &mut seat_account.data.borrow_mut()[..])?;
+
+    // Resolve the wager once every seat agrees on the outcome, and open a
+    // dispute the first time every seat has judged without agreeing
+    if let Some(outcome) = consensus(&seats) {
+        msg!("Players agree: {:?}", outcome);
+        wager.outcome = outcome;
+        wager.advance(Phase::Judging)?;
+    } else if wager.phase == Phase::Disputed {
+        msg!("Dispute amended");
+    } else if all_judged(&seats) {
+        wager.open_dispute(clock.unix_timestamp)?;
+        msg!("Players disagree, disputed until {}", wager.disputed_until);
+    } else {
+        wager.advance(Phase::Judging)?;
+    }
+
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Records an oracle's outcome for a contract; the attestation PDA is derived
+// from the signing oracle, so nobody else can publish in its name
+pub fn publish_attestation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    contract: Pubkey,
+    outcome: Judgment,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle = next_account_info(accounts_iter)?;
+    let attestation_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // Verify signer
+    if !oracle.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // An attestation has to decide something
+    if outcome == Judgment::Pending {
+        return Err(WagerError::InvalidAttestation.into());
+    }
+
+    let (pda, bump) = Pubkey::find_program_address(
+        &[b"attestation", oracle.key.as_ref(), contract.as_ref()],
+        program_id
+    );
+
+    if pda != *attestation_account.key {
+        return Err(WagerError::InvalidAttestation.into());
+    }
+
+    // Published outcomes are final
+    if attestation_account.owner == program_id {
+        return Err(WagerError::AlreadyResolved.into());
+    }
+
+    let clock = Clock::get()?;
+    let attestation = Attestation {
+        discriminator: Attestation::DISCRIMINATOR,
+        version: Attestation::VERSION,
+        oracle: *oracle.key,
+        contract,
+        bump,
+        outcome,
+        timestamp: clock.unix_timestamp,
+    };
+
+    create_pda_account(
+        oracle,
+        attestation_account,
+        system_program,
+        Attestation::SPACE,
+        program_id,
+        &[b"attestation", oracle.key.as_ref(), contract.as_ref(), &[bump]],
+    )?;
+
+    attestation.serialize(&mut &mut attestation_account.data.borrow_mut()[..])?;
+
+    msg!("Oracle attested {:?}", outcome);
+
+    Ok(())
+}
+
+// Settles the outcome of a locked wager from its oracle's attestation,
+// without waiting on seat judgments
+pub fn resolve_from_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let attestation_account = next_account_info(accounts_iter)?;
+
+    // Verify account ownership
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signer
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // A resolved wager cannot be re-judged
+    if wager.outcome != Judgment::Pending {
+        return Err(WagerError::AlreadyResolved.into());
+    }
+
+    // Outcomes only count once every seat agreed to the terms
+    if wager.phase == Phase::Created || wager.phase == Phase::Funding {
+        return Err(WagerError::WagerNotLocked.into());
+    }
+
+    // The oracle outranks seat judgments, disputed or not
+    wager.expect_phase(&[Phase::Locked, Phase::Judging, Phase::Disputed])?;
+
+    let outcome = attested_outcome(program_id, &wager, attestation_account)?;
+
+    msg!("Oracle resolved: {:?}", outcome);
+    wager.outcome = outcome;
+
+    wager.advance(Phase::Judging)?;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Lets the wager's arbiter decide the outcome of an escalated dispute, or
+// once the resolve deadline passed without consensus
+pub fn arbiter_judgment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    judgment: Judgment,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let arbiter = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+
+    // Verify account ownership
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signer
+    if !arbiter.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // The arbiter has to decide something
+    if judgment == Judgment::Pending {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // A resolved wager cannot be re-judged
+    if wager.outcome != Judgment::Pending {
+        return Err(WagerError::AlreadyResolved.into());
+    }
+
+    // Rulings are only meaningful once every seat agreed to the terms
+    if wager.phase == Phase::Created || wager.phase == Phase::Funding {
+        return Err(WagerError::WagerNotLocked.into());
+    }
+
+    wager.expect_phase(&[Phase::Locked, Phase::Judging, Phase::Disputed])?;
+
+    if !wager.has_arbiter() {
+        return Err(WagerError::ArbiterNotConfigured.into());
+    }
+
+    if wager.arbiter != *arbiter.key {
+        return Err(WagerError::NotWagerArbiter.into());
+    }
+
+    // Seats get the first say; the arbiter steps in when a dispute escalates
+    // to it or judging stalls past the resolve deadline
+    let clock = Clock::get()?;
+    let may_rule = if wager.phase == Phase::Disputed {
+        wager.fallback == Fallback::Arbiter && wager.dispute_expired(clock.unix_timestamp)
+    } else {
+        clock.unix_timestamp > wager.resolve_deadline
+    };
+
+    if !may_rule {
+        return Err(WagerError::ArbitrationNotAllowed.into());
+    }
+
+    msg!("Arbiter ruled: {:?}", judgment);
+    wager.outcome = judgment;
+
+    wager.advance(Phase::Judging)?;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Applies the wager's fallback to a dispute whose window closed without
+// consensus, or to judging left unfinished at the resolve deadline; arbiter
+// fallbacks are ruled on with ArbiterJudgment instead, until the settle
+// deadline passes and any wager still unresolved pushes
+pub fn escalate_dispute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+
+    // Verify account ownership
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signer
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+    wager.expect_phase(&[Phase::Locked, Phase::Judging, Phase::Disputed])?;
+
+    if wager.outcome != Judgment::Pending {
+        return Err(WagerError::AlreadyResolved.into());
+    }
+
+    // Disputes escalate once their window closes; judging that stalls, such
+    // as a losing seat never judging, escalates past the resolve deadline
+    let clock = Clock::get()?;
+    let may_escalate = if wager.phase == Phase::Disputed {
+        wager.dispute_expired(clock.unix_timestamp)
+    } else {
+        clock.unix_timestamp > wager.resolve_deadline
+    };
+
+    if !may_escalate {
+        return Err(WagerError::DisputeOpen.into());
+    }
+
+    // An arbiter that never rules or an oracle that never publishes cannot
+    // hold the stakes forever
+    let outcome = if clock.unix_timestamp > wager.settle_deadline() {
+        Judgment::Push
     } else {
-        return Err(ProgramError::InvalidArgument);
+        match wager.fallback {
+            Fallback::Push => Judgment::Push,
+            Fallback::Oracle => {
+                let attestation_account = next_account_info(accounts_iter)?;
+                attested_outcome(program_id, &wager, attestation_account)?
+            }
+            Fallback::Arbiter => return Err(WagerError::InvalidFallback.into()),
+        }
     };
 
-    seat.status = Status::Locked;
+    msg!("Dispute escalated: {:?}", outcome);
+    wager.outcome = outcome;
 
-    // Serialize updated data back to account
-    versus.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+    wager.advance(Phase::Judging)?;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
 
     Ok(())
 }
 
-pub fn set_judgment(
+// Outcome published by the wager's oracle for its contract
+fn attested_outcome(
+    program_id: &Pubkey,
+    wager: &Wager,
+    attestation_account: &AccountInfo,
+) -> Result<Judgment, ProgramError> {
+    if attestation_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if !wager.has_oracle() {
+        return Err(WagerError::OracleNotConfigured.into());
+    }
+
+    let attestation = Attestation::load(&attestation_account.data.borrow())?;
+
+    // Verify the attestation is the oracle's own PDA for this contract
+    let attestation_pda = Pubkey::create_program_address(
+        &[b"attestation", wager.oracle.as_ref(), wager.contract.as_ref(), &[attestation.bump]],
+        program_id
+    ).map_err(|_| WagerError::InvalidAttestation)?;
+
+    if attestation_pda != *attestation_account.key {
+        return Err(WagerError::InvalidAttestation.into());
+    }
+
+    if attestation.oracle != wager.oracle || attestation.contract != wager.contract {
+        return Err(WagerError::InvalidAttestation.into());
+    }
+
+    // Outcomes observed after the resolve deadline are not part of the terms
+    if attestation.timestamp > wager.resolve_deadline {
+        return Err(WagerError::DeadlinePassed.into());
+    }
+
+    Ok(attestation.outcome)
+}
+
+pub fn render_payout(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    judgment: Judgment,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let signer = next_account_info(accounts_iter)?;
     let wager_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let transfer_program = next_account_info(accounts_iter)?;
+    let remaining_accounts = accounts_iter.as_slice();
 
     // Verify account ownership
     if wager_account.owner != program_id {
         return Err(ProgramError::IncorrectProgramId);
     }
-    
+
     // Verify signer
     if !signer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
-    
+
     // Deserialize account data
-    let mut versus = Wager::try_from_slice(&wager_account.data.borrow())?;
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // Settlement only ever happens once, though it may take several batches
+    if wager.phase == Phase::Settled && wager.payouts_finished() {
+        return Err(WagerError::AlreadySettled.into());
+    }
 
-    // Find matching seat
-    let seat = if signer.key == &versus.seat_a.wallet {
-        &mut versus.seat_a
-    } else if signer.key == &versus.seat_b.wallet {
-        &mut versus.seat_b
+    // Payouts are impossible until every seat agreed to the terms
+    if wager.phase == Phase::Created || wager.phase == Phase::Funding {
+        return Err(WagerError::WagerNotLocked.into());
+    }
+
+    // Disputes settle through amendments or their fallback first
+    if wager.phase == Phase::Disputed {
+        return Err(WagerError::JudgmentsDisagree.into());
+    }
+
+    wager.expect_phase(&[Phase::Locked, Phase::Judging, Phase::Settled])?;
+
+    // Payouts follow the outcome, so it has to be decided first
+    if wager.outcome == Judgment::Pending {
+        return Err(WagerError::WagerNotResolved.into());
+    }
+
+    check_transfer_program(&wager, transfer_program)?;
+    check_vault(program_id, wager_account.key, &wager, vault_account, transfer_program)?;
+
+    let PayoutAccounts {
+        seats: seat_accounts,
+        recipients: authority_accounts,
+        mint: mint_account,
+    } = split_payout_accounts(remaining_accounts, !wager.is_native())?;
+
+    if let Some(mint_account) = mint_account {
+        load_mint(&wager, mint_account, transfer_program)?;
+    }
+
+    // Each batch picks up at the first seat not paid yet
+    let seats = load_seat_batch(program_id, wager_account.key, &wager, seat_accounts, wager.cursor)?;
+
+    let gross = seats
+        .iter()
+        .map(|seat| seat_payout(&wager, seat))
+        .collect::<Result<Vec<u64>, ProgramError>>()?;
+    let payouts = batch_shares(&mut wager, gross)?;
+
+    for ((seat, authority), amount) in seats.iter().zip(authority_accounts).zip(payouts) {
+        check_recipient(&wager, seat, authority, transfer_program)?;
+
+        if amount == 0 {
+            continue;
+        }
+
+        msg!("Paying {} to {}", amount, authority.key);
+
+        pay_from_vault(wager_account.key, &wager, vault_account, authority, transfer_program, mint_account, amount)?;
+    }
+
+    wager.advance(Phase::Settled)?;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    if wager.payouts_finished() {
+        msg!("Wager settled!");
     } else {
-        return Err(ProgramError::InvalidArgument);
-    };
+        msg!("Paid seats up to {}", wager.cursor);
+    }
 
-    seat.judgment = judgment;
-    
-    // Serialize updated data back to account
-    versus.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// Backs out of a wager before it locks, refunding every staked seat
+pub fn cancel_wager(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let wager_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let transfer_program = next_account_info(accounts_iter)?;
+    let remaining_accounts = accounts_iter.as_slice();
+
+    // Verify account ownership
+    if wager_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signer
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // Later batches of refunds carry on a cancel that was already allowed
+    let continuing = wager.phase == Phase::Cancelled && !wager.payouts_finished();
+
+    // Once every seat has locked the wager has to play out
+    if !continuing {
+        wager.expect_phase(&[Phase::Created, Phase::Funding])?;
+    }
+
+    check_transfer_program(&wager, transfer_program)?;
+    check_vault(program_id, wager_account.key, &wager, vault_account, transfer_program)?;
+
+    let PayoutAccounts {
+        seats: seat_accounts,
+        recipients: authority_accounts,
+        mint: mint_account,
+    } = split_payout_accounts(remaining_accounts, !wager.is_native())?;
+
+    if let Some(mint_account) = mint_account {
+        load_mint(&wager, mint_account, transfer_program)?;
+    }
+
+    // Each batch picks up at the first seat not refunded yet
+    let seats = load_seat_batch(program_id, wager_account.key, &wager, seat_accounts, wager.cursor)?;
+
+    // The creator can always cancel; seat holders only while nobody has
+    // locked, and with their own seat in the first batch
+    if !continuing {
+        let is_creator = wager.creator == *signer.key;
+        let is_seat_holder = seats.iter().any(|seat| seat.authority == *signer.key);
+        let any_locked = wager.locked_count > 0;
+
+        let may_cancel = is_creator || (is_seat_holder && !any_locked);
+
+        if !may_cancel {
+            return Err(WagerError::CancelNotAllowed.into());
+        }
+    }
+
+    // Open seats never paid in; staked seats get back their share of what arrived
+    let stakes = seats
+        .iter()
+        .map(|seat| if seat.status == Status::Open { 0 } else { wager.stake })
+        .collect();
+    let refunds = batch_shares(&mut wager, stakes)?;
+
+    for ((seat, authority), amount) in seats.iter().zip(authority_accounts).zip(refunds) {
+        check_recipient(&wager, seat, authority, transfer_program)?;
+
+        if amount == 0 {
+            continue;
+        }
+
+        msg!("Refunding {} to {}", amount, authority.key);
+
+        pay_from_vault(wager_account.key, &wager, vault_account, authority, transfer_program, mint_account, amount)?;
+    }
+
+    wager.advance(Phase::Cancelled)?;
+    wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+
+    if wager.payouts_finished() {
+        msg!("Wager cancelled!");
+    } else {
+        msg!("Refunded seats up to {}", wager.cursor);
+    }
 
     Ok(())
 }
 
-pub fn render_payout(
+// Reclaims the rent of a finished wager, its seats and its vault for whoever
+// paid it. Seats close in batches from the last one down, and the batch that
+// closes seat 0 also closes the vault and the wager
+pub fn close_wager(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
@@ -419,7 +1631,9 @@ pub fn render_payout(
     let signer = next_account_info(accounts_iter)?;
     let wager_account = next_account_info(accounts_iter)?;
     let vault_account = next_account_info(accounts_iter)?;
-    let system_program = next_account_info(accounts_iter)?;
+    let creator = next_account_info(accounts_iter)?;
+    let transfer_program = next_account_info(accounts_iter)?;
+    let remaining_accounts = accounts_iter.as_slice();
 
     // Verify account ownership
     if wager_account.owner != program_id {
@@ -431,94 +1645,577 @@ pub fn render_payout(
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    if vault_account.owner != &system_program::ID {
+    let mut wager = Wager::load(&wager_account.data.borrow())?;
+
+    // Only wagers nobody can act on anymore are closed
+    wager.expect_phase(&[Phase::Settled, Phase::Cancelled])?;
+
+    // Seats still owed a payout keep the wager open
+    if !wager.payouts_finished() {
+        return Err(WagerError::PayoutsPending.into());
+    }
+
+    // Rent goes back to whoever paid it
+    if wager.creator != *creator.key {
+        return Err(WagerError::NotWagerCreator.into());
+    }
+
+    check_transfer_program(&wager, transfer_program)?;
+    check_vault(program_id, wager_account.key, &wager, vault_account, transfer_program)?;
+
+    // Token wagers pass the mint and a token account of the creator last
+    let (remaining_accounts, token_accounts) = if wager.is_native() {
+        (remaining_accounts, None)
+    } else {
+        let split = remaining_accounts
+            .len()
+            .checked_sub(2)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let (remaining_accounts, token_accounts) = remaining_accounts.split_at(split);
+        (remaining_accounts, Some((&token_accounts[0], &token_accounts[1])))
+    };
+
+    // Each seat in the batch is matched by whoever gets its rent back
+    let PayoutAccounts {
+        seats: seat_accounts,
+        recipients: rent_recipients,
+        ..
+    } = split_payout_accounts(remaining_accounts, false)?;
+
+    // The batch is always the last seats still open
+    let start = (wager.seat_count as usize)
+        .checked_sub(seat_accounts.len())
+        .ok_or(WagerError::SeatCountMismatch)? as u8;
+
+    // Loading every seat proves they all belong to this wager
+    let seats = load_seat_batch(program_id, wager_account.key, &wager, seat_accounts, start)?;
+
+    // Reserved seats were paid for by the creator, joined seats by their holder
+    for (offset, (seat, recipient)) in seats.iter().zip(rent_recipients).enumerate() {
+        let reserved = (start as usize + offset) < wager.reserved_count as usize;
+        let rent_payer = if reserved { &wager.creator } else { &seat.authority };
+
+        if rent_payer != recipient.key {
+            return Err(WagerError::WrongRentRecipient.into());
+        }
+    }
+
+    // Earlier batches only close seats; the one reaching seat 0 also closes
+    // the vault and the wager
+    if start > 0 {
+        for (seat_account, recipient) in seat_accounts.iter().zip(rent_recipients) {
+            close_account(seat_account, recipient)?;
+        }
+
+        wager.seat_count = start;
+        wager.serialize(&mut &mut wager_account.data.borrow_mut()[..])?;
+        msg!("Closed seats down to {}", start);
+        return Ok(());
+    }
+
+    if let Some((mint_account, creator_token_account)) = token_accounts {
+        load_mint(&wager, mint_account, transfer_program)?;
+
+        check_creator_token_account(&wager, creator_token_account, transfer_program)?;
+
+        // Fees withheld in the vault block closing it until they move to the mint
+        if withheld_fees(vault_account)? > 0 {
+            invoke(
+                &spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                    transfer_program.key,
+                    mint_account.key,
+                    &[vault_account.key],
+                )?,
+                &[
+                    mint_account.clone(),
+                    vault_account.clone(),
+                    transfer_program.clone(),
+                ],
+            )?;
+        }
+
+        // Anyone can send tokens to the vault, and a balance blocks closing it
+        let remainder = token_balance(vault_account)?;
+        if remainder > 0 {
+            msg!("Sweeping {} tokens from vault", remainder);
+            pay_from_vault(
+                wager_account.key,
+                &wager,
+                vault_account,
+                creator_token_account,
+                transfer_program,
+                Some(mint_account),
+                remainder,
+            )?;
+        }
+
+        let vault_seeds: &[&[u8]] = &[b"vault", wager_account.key.as_ref(), &[wager.vault_bump]];
+
+        invoke_signed(
+            &spl_token_2022::instruction::close_account(
+                transfer_program.key,
+                vault_account.key,
+                creator.key,
+                vault_account.key,
+                &[],
+            )?,
+            &[
+                vault_account.clone(),
+                creator.clone(),
+                vault_account.clone(),
+                transfer_program.clone(),
+            ],
+            &[vault_seeds],
+        )?;
+    } else {
+        let remainder = vault_account.lamports();
+        if remainder > 0 {
+            msg!("Draining {} lamports from vault", remainder);
+            pay_from_vault(wager_account.key, &wager, vault_account, creator, transfer_program, None, remainder)?;
+        }
+    }
+
+    for (seat_account, recipient) in seat_accounts.iter().zip(rent_recipients) {
+        close_account(seat_account, recipient)?;
+    }
+
+    close_account(wager_account, creator)?;
+
+    msg!("Wager closed!");
+
+    Ok(())
+}
+
+// Zeroes a program-owned account and hands its lamports to `recipient`
+fn close_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+
+    account.data.borrow_mut().fill(0);
+    account.resize(0)?;
+    account.assign(&system_program::ID);
+
+    Ok(())
+}
+
+// Lamport wagers move stakes with the system program, token wagers with
+// either the legacy token program or Token-2022
+fn check_transfer_program(wager: &Wager, transfer_program: &AccountInfo) -> ProgramResult {
+    let valid = if wager.is_native() {
+        *transfer_program.key == system_program::ID
+    } else {
+        is_token_program(transfer_program.key)
+    };
+
+    if !valid {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    Ok(())
+}
+
+fn is_token_program(key: &Pubkey) -> bool {
+    *key == spl_token::ID || *key == spl_token_2022::ID
+}
+
+// Verifies the vault PDA against the stored bump and the program that owns it
+fn check_vault(
+    program_id: &Pubkey,
+    wager_key: &Pubkey,
+    wager: &Wager,
+    vault_account: &AccountInfo,
+    transfer_program: &AccountInfo,
+) -> ProgramResult {
+    if vault_account.owner != transfer_program.key {
         msg!("Vault owner is wrong!");
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    // Deserialize account data
-    let wager = Wager::try_from_slice(&wager_account.data.borrow())?;
+    let vault_pda = Pubkey::create_program_address(
+        &[b"vault", wager_key.as_ref(), &[wager.vault_bump]],
+        program_id
+    )?;
+
+    if vault_pda != *vault_account.key {
+        return Err(WagerError::InvalidVault.into());
+    }
+
+    Ok(())
+}
+
+// Verifies the mint of a token wager and returns its decimals for transfer_checked
+fn load_mint(wager: &Wager, mint_account: &AccountInfo, token_program: &AccountInfo) -> Result<u8, ProgramError> {
+    if *mint_account.key != wager.mint || mint_account.owner != token_program.key {
+        return Err(WagerError::InvalidMint.into());
+    }
+
+    let data = mint_account.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(mint.base.decimals)
+}
+
+fn token_balance(token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = token_account.data.borrow();
+    Ok(StateWithExtensions::<TokenAccount>::unpack(&data)?.base.amount)
+}
+
+// Transfer fees a Token-2022 account holds back; legacy accounts never withhold
+fn withheld_fees(token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    let data = token_account.data.borrow();
+    let account = StateWithExtensions::<TokenAccount>::unpack(&data)?;
+
+    Ok(account
+        .get_extension::<TransferFeeAmount>()
+        .map(|fee| u64::from(fee.withheld_amount))
+        .unwrap_or(0))
+}
+
+// Payouts go to the seat authority itself, or to a token account it owns
+fn check_recipient(
+    wager: &Wager,
+    seat: &Seat,
+    recipient: &AccountInfo,
+    transfer_program: &AccountInfo,
+) -> ProgramResult {
+    if wager.is_native() {
+        if seat.authority != *recipient.key {
+            return Err(WagerError::WrongSeatAuthority.into());
+        }
+        return Ok(());
+    }
+
+    if recipient.owner != transfer_program.key {
+        return Err(WagerError::InvalidMint.into());
+    }
+
+    let data = recipient.data.borrow();
+    let token_account = StateWithExtensions::<TokenAccount>::unpack(&data)?.base;
+
+    if token_account.mint != wager.mint {
+        return Err(WagerError::InvalidMint.into());
+    }
+
+    if token_account.owner != seat.authority {
+        return Err(WagerError::WrongSeatAuthority.into());
+    }
 
-    let payouts = calc_risk(wager.stake, seat.belief as u64, seat.belief as u64);
-    let (risk_a, risk_b) = payouts;
+    Ok(())
+}
+
+// Tokens left in a closing vault go to a token account the creator owns
+fn check_creator_token_account(
+    wager: &Wager,
+    token_account: &AccountInfo,
+    transfer_program: &AccountInfo,
+) -> ProgramResult {
+    if token_account.owner != transfer_program.key {
+        return Err(WagerError::InvalidMint.into());
+    }
+
+    let data = token_account.data.borrow();
+    let token_account = StateWithExtensions::<TokenAccount>::unpack(&data)?.base;
+
+    if token_account.mint != wager.mint {
+        return Err(WagerError::InvalidMint.into());
+    }
 
-    /*
-    // Find matching wallet
-    let player_wallet = if signer.key == &versus.seat_a.wallet {
-        &versus.seat_a.wallet
-    } else if signer.key == &versus.seat_b.wallet {
-        &versus.seat_b.wallet
+    if token_account.owner != wager.creator {
+        return Err(WagerError::NotWagerCreator.into());
+    }
+
+    Ok(())
+}
+
+// Trailing accounts of instructions that pay out of the vault
+struct PayoutAccounts<'b, 'a> {
+    seats: &'b [AccountInfo<'a>],
+    recipients: &'b [AccountInfo<'a>],
+    mint: Option<&'b AccountInfo<'a>>,
+}
+
+// A batch of seats comes first, followed by each seat's recipient in the same
+// order: the authority itself, or its token account for token wagers, which
+// also pass the mint last
+fn split_payout_accounts<'b, 'a>(
+    accounts: &'b [AccountInfo<'a>],
+    has_mint: bool,
+) -> Result<PayoutAccounts<'b, 'a>, ProgramError> {
+    let (accounts, mint_account) = if has_mint {
+        let (mint_account, accounts) = accounts
+            .split_last()
+            .ok_or(WagerError::SeatCountMismatch)?;
+        (accounts, Some(mint_account))
     } else {
-        return Err(ProgramError::InvalidArgument);
+        (accounts, None)
     };
-    */
-    
-    // Determine if players agree on wager outcome
-    let _judgment = if 
-        versus.seat_a.judgment == Judgment::Landed &&  
-        versus.seat_b.judgment == Judgment::Landed {
-        msg!("Players agree: Wager Landed!");
-        Judgment::Landed
-    } else if
-        versus.seat_a.judgment == Judgment::Missed && 
-        versus.seat_b.judgment == Judgment::Missed {
-        msg!("Players agree: Wager Missed!");
-        Judgment::Missed
-    } else if 
-        versus.seat_a.judgment == Judgment::Push && 
-        versus.seat_b.judgment == Judgment::Push {
-        msg!("Players agree to push");
-        Judgment::Push
-    } else {
-        return Err(ProgramError::InvalidAccountData)
+
+    if accounts.len() % 2 != 0 {
+        return Err(WagerError::SeatCountMismatch.into());
+    }
+
+    let (seats, recipients) = accounts.split_at(accounts.len() / 2);
+    Ok(PayoutAccounts { seats, recipients, mint: mint_account })
+}
+
+// Moves stakes out of the vault PDA, signing with the wager's stored bump
+fn pay_from_vault<'a>(
+    wager_key: &Pubkey,
+    wager: &Wager,
+    vault_account: &AccountInfo<'a>,
+    recipient: &AccountInfo<'a>,
+    transfer_program: &AccountInfo<'a>,
+    mint_account: Option<&AccountInfo<'a>>,
+    amount: u64,
+) -> ProgramResult {
+    let vault_seeds: &[&[u8]] = &[b"vault", wager_key.as_ref(), &[wager.vault_bump]];
+
+    let Some(mint_account) = mint_account else {
+        return invoke_signed(
+            &system_instruction::transfer(
+                vault_account.key,
+                recipient.key,
+                amount,
+            ),
+            &[
+                vault_account.clone(),
+                recipient.clone(),
+                transfer_program.clone(),
+            ],
+            &[vault_seeds],
+        );
     };
 
-    msg!("Player {} {} {}", player_wallet, risk_a, risk_b);
+    let decimals = load_mint(wager, mint_account, transfer_program)?;
 
     invoke_signed(
-        &system_instruction::transfer(
+        &spl_token_2022::instruction::transfer_checked(
+            transfer_program.key,
             vault_account.key,
-            signer.key,
-            versus.wager.stake,
-        ),
+            mint_account.key,
+            recipient.key,
+            vault_account.key,
+            &[],
+            amount,
+            decimals,
+        )?,
         &[
             vault_account.clone(),
-            signer.clone(),
-            system_program.clone(),
+            mint_account.clone(),
+            recipient.clone(),
+            vault_account.clone(),
+            transfer_program.clone(),
         ],
-        &[&[b"vault", wager_account.key.as_ref(), &[versus.wager.vault_bump]]]
-    )?;
+        &[vault_seeds],
+    )
+}
+
+// Upgrades a v0 Wager or Seat, which predates the account header, in place.
+// Wagers also pass their first seat, whose holder becomes the creator so the
+// wager's rent can be reclaimed on close
+pub fn migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let first_seat = accounts_iter.next();
+
+    // Verify signer
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify account ownership
+    if account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if *system_program.key != system_program::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // v0 wagers and seats are both 75 bytes, which no current layout is
+    let upgraded = {
+        let data = account.data.borrow();
+        if data.len() != WagerV0::SPACE {
+            return Err(WagerError::NotLegacyAccount.into());
+        }
+
+        // A v0 wager records the vault PDA derived from its own address; a
+        // seat has its authority there, so anything else must be a seat PDA
+        let wager = WagerV0::try_from_slice(&data)?;
+        let vault = Pubkey::create_program_address(
+            &[b"vault", account.key.as_ref(), &[wager.vault_bump]],
+            program_id,
+        );
+
+        if vault == Ok(wager.vault) {
+            // A wager nobody holds a seat in pays back whoever migrates it
+            let creator = if wager.seat_count == 0 {
+                *payer.key
+            } else {
+                let first_seat = first_seat.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                legacy_seat_holder(program_id, account.key, first_seat)?
+            };
+
+            to_vec(&Wager { creator, ..Wager::from(wager) })?
+        } else {
+            let legacy = SeatV0::try_from_slice(&data)?;
+            let bump = legacy_seat_bump(program_id, &legacy.wager, account.key)?;
+            to_vec(&Seat { bump, ..Seat::from(legacy) })?
+        }
+    };
+
+    // Top up rent for the larger layout
+    let rent = Rent::get()?;
+    let required_lamports = rent.minimum_balance(upgraded.len());
+    let shortfall = required_lamports.saturating_sub(account.lamports());
+
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(
+                payer.key,
+                account.key,
+                shortfall,
+            ),
+            &[
+                payer.clone(),
+                account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    account.resize(upgraded.len())?;
+    account.data.borrow_mut().copy_from_slice(&upgraded);
+
+    msg!("Account migrated!");
 
     Ok(())
 }
 
-fn calc_risk(stake: u64, belief_a: u64, belief_b: u64) -> (u64, u64) {
+// Holder of seat 0 of a v0 wager, whether or not that seat was migrated first
+fn legacy_seat_holder(program_id: &Pubkey, wager_key: &Pubkey, seat_account: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    if seat_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (seat_pda, _) = Pubkey::find_program_address(
+        &[b"seat", wager_key.as_ref(), &0u8.to_le_bytes()],
+        program_id
+    );
+
+    if seat_pda != *seat_account.key {
+        return Err(WagerError::InvalidSeat.into());
+    }
 
-    if belief_a == belief_b { return (0, 0) };
+    let data = seat_account.data.borrow();
+    let (wager, authority) = match Seat::load(&data) {
+        Ok(seat) => (seat.wager, seat.authority),
+        Err(_) => {
+            let legacy = SeatV0::try_from_slice(&data)?;
+            (legacy.wager, legacy.authority)
+        }
+    };
 
-    let p = cmp::max(belief_a, belief_b);
-    let q = 100 - cmp::min(belief_a, belief_b);
+    if wager != *wager_key {
+        return Err(WagerError::InvalidSeat.into());
+    }
 
-    // max 10000
-    let p_sqd = p * p;
-    let q_sqd = q * q;
+    Ok(authority)
+}
 
-    let p_surprise = 100 - p;
-    let q_surprise = 100 - q;
+// v0 seats never stored their index, so the bump is found by trying each
+// index in turn; v0 wagers were heads-up, so this ends after one or two
+fn legacy_seat_bump(program_id: &Pubkey, wager_key: &Pubkey, seat_key: &Pubkey) -> Result<u8, ProgramError> {
+    (0..=u8::MAX)
+        .map(|index| Pubkey::find_program_address(
+            &[b"seat", wager_key.as_ref(), &index.to_le_bytes()],
+            program_id
+        ))
+        .find(|(pda, _)| pda == seat_key)
+        .map(|(_, bump)| bump)
+        .ok_or_else(|| WagerError::InvalidSeat.into())
+}
 
-    // max 10000
-    let p_surprise_sqd = p_surprise * p_surprise;
-    let q_surprise_sqd = q_surprise * q_surprise;
+// Squared distance of a belief from the outcome that came about
+fn belief_score(outcome: Judgment, belief: u8) -> u32 {
+    let distance = match outcome {
+        Judgment::Landed => MAX_BELIEF.saturating_sub(belief),
+        _ => belief,
+    } as u32;
+    distance * distance
+}
 
-    let portion_a = p_sqd - q_surprise_sqd;
-    let portion_b = q_sqd - p_surprise_sqd;
+// What a seat takes from the pot under the wager's outcome. Every seat is
+// scored against every other, putting an equal share of its stake into each
+// pairing; a pairing moves that share, scaled by the difference between the
+// two seats' scores, to the closer seat. Summed over its pairings that only
+// needs the totals recorded when the wager locked, so seats can be paid in
+// any batch
+fn seat_payout(wager: &Wager, seat: &Seat) -> Result<u64, ProgramError> {
+    let total = match wager.outcome {
+        Judgment::Pending => return Err(WagerError::WagerNotResolved.into()),
+        Judgment::Push => return Ok(wager.stake),
+        Judgment::Landed => wager.doubt_squares,
+        Judgment::Missed => wager.belief_squares,
+    };
 
-    let scale = stake / 10_000; // 100^2
+    // Two seats stake everything on their one pairing
+    let seat_count = wager.seat_count as i128;
+    let share = wager.stake / (wager.seat_count as u64).saturating_sub(1).max(1);
+    let scale = (share / 10_000) as i128; // 100^2
 
-    let risk_a = scale * portion_a;
-    let risk_b = scale * portion_b;
+    let score = belief_score(wager.outcome, seat.belief) as i128;
+    let payout = wager.stake as i128 + scale * (total as i128 - seat_count * score);
 
-    return (risk_a, risk_b);
+    u64::try_from(payout).map_err(|_| ProgramError::ArithmeticOverflow)
 }
 
-*/
\ No newline at end of file
+// Scales a batch of gross payouts, which assume every stake arrived in full,
+// down to the pot the vault actually holds and moves the cursor past the
+// batch. Rounding dust goes to the last paid seat of the final batch
+fn batch_shares(wager: &mut Wager, gross: Vec<u64>) -> Result<Vec<u64>, ProgramError> {
+    // Lamport stakes always arrive in full
+    let mut shares = if wager.is_native() {
+        gross
+    } else {
+        let total = wager.stake as u128 * wager.staked_count as u128;
+        gross
+            .iter()
+            .map(|&amount| (amount as u128 * wager.pot as u128 / total.max(1)) as u64)
+            .collect()
+    };
+
+    let mut paid = shares
+        .iter()
+        .try_fold(wager.paid, |paid, &share| paid.checked_add(share))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    wager.cursor = wager
+        .cursor
+        .checked_add(shares.len() as u8)
+        .ok_or(WagerError::SeatCountMismatch)?;
+
+    if wager.payouts_finished() && !wager.is_native() {
+        if let Some(share) = shares.iter_mut().rev().find(|share| **share > 0) {
+            let dust = wager
+                .pot
+                .checked_sub(paid)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            *share += dust;
+            paid += dust;
+        }
+    }
+
+    wager.paid = paid;
+    Ok(shares)
+}
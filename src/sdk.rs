@@ -0,0 +1,736 @@
+// sdk.rs
+
+// Off-chain builders for every WagerInstruction, listing accounts in the
+// exact order processor.rs reads them
+
+use crate::instruction::WagerInstruction;
+use crate::state::{Wager, Attestation, Phase, Fallback, Judgment};
+
+use borsh::to_vec;
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use solana_sdk_ids::system_program;
+
+pub fn find_counter_address(program_id: &Pubkey, creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"counter", creator.as_ref()], program_id)
+}
+
+// `nonce` counts the creator's wagers from 0; the next one is the counter's
+// wager_count, or 0 before the counter exists
+pub fn find_wager_address(program_id: &Pubkey, creator: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"wager", creator.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+// Every wager `creator` has made, given the wager_count of their counter
+pub fn creator_wager_addresses(program_id: &Pubkey, creator: &Pubkey, wager_count: u64) -> Vec<Pubkey> {
+    (0..wager_count)
+        .map(|nonce| find_wager_address(program_id, creator, nonce).0)
+        .collect()
+}
+
+pub fn find_vault_address(program_id: &Pubkey, wager: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault", wager.as_ref()], program_id)
+}
+
+pub fn find_attestation_address(program_id: &Pubkey, oracle: &Pubkey, contract: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"attestation", oracle.as_ref(), contract.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_seat_address(program_id: &Pubkey, wager: &Pubkey, index: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"seat", wager.as_ref(), &index.to_le_bytes()],
+        program_id,
+    )
+}
+
+// Offset and bytes of an RPC memcmp filter matching wagers in `phase`;
+// the header is included so seat accounts never match
+pub fn phase_filter(phase: Phase) -> (usize, Vec<u8>) {
+    let mut bytes = Wager::DISCRIMINATOR.to_vec();
+    bytes.push(Wager::VERSION);
+    bytes.push(phase as u8);
+    (0, bytes)
+}
+
+fn seat_addresses(program_id: &Pubkey, wager: &Pubkey, seat_count: u8) -> Vec<Pubkey> {
+    (0..seat_count)
+        .map(|index| find_seat_address(program_id, wager, index).0)
+        .collect()
+}
+
+fn instruction(program_id: &Pubkey, data: &WagerInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    let encoded_data = to_vec(data).expect("instruction serializes");
+    Instruction::new_with_bytes(*program_id, &encoded_data, accounts)
+}
+
+// Mint of a token wager and the program that owns it, either the legacy
+// token program or Token-2022
+#[derive(Clone, Copy, Debug)]
+pub struct TokenMint {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+}
+
+// Caller-chosen terms of a new wager; everything else is derived
+#[derive(Clone, Debug)]
+pub struct WagerTerms {
+    pub contract: Pubkey,
+    pub mint: Option<TokenMint>,    // None stakes native lamports
+    pub oracle: Option<Pubkey>,     // None leaves resolution to the seats
+    pub arbiter: Option<Pubkey>,    // None leaves deadlocks unresolved
+    pub capacity: u8,
+    pub stake: u64,
+    pub join_deadline: i64,
+    pub lock_deadline: i64,
+    pub resolve_deadline: i64,
+    pub fallback: Fallback,         // applied when seats stay in dispute
+    pub dispute_window: i64,        // seconds seats get to amend a dispute
+}
+
+// `wager` must be find_wager_address(program_id, payer, nonce) for the payer's
+// next nonce; the payer's counter is created along with their first wager
+pub fn create_wager_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    wager: &Pubkey,
+    terms: &WagerTerms,
+    reserved_seats: &[Pubkey],
+) -> Instruction {
+    let reserved_count = reserved_seats.len() as u8;
+    create_batched_wager_ix(program_id, payer, wager, terms, reserved_count, reserved_seats)
+}
+
+// Reserves `reserved_count` seats but only creates those in `first_seats`;
+// the rest follow with init_seats_ix before anyone can stake
+pub fn create_batched_wager_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    wager: &Pubkey,
+    terms: &WagerTerms,
+    reserved_count: u8,
+    first_seats: &[Pubkey],
+) -> Instruction {
+    let (vault, vault_bump) = find_vault_address(program_id, wager);
+
+    let data = WagerInstruction::CreateWager {
+        wager: Wager {
+            discriminator: Wager::DISCRIMINATOR,
+            version: Wager::VERSION,
+            phase: Phase::Created,
+            creator: *payer,
+            contract: terms.contract,
+            mint: terms.mint.map(|mint| mint.mint).unwrap_or_default(),
+            oracle: terms.oracle.unwrap_or_default(),
+            arbiter: terms.arbiter.unwrap_or_default(),
+            vault,
+            vault_bump,
+            seat_count: 0,
+            reserved_count,
+            staked_count: 0,
+            locked_count: 0,
+            capacity: terms.capacity,
+            stake: terms.stake,
+            pot: 0,
+            paid: 0,
+            cursor: 0,
+            join_deadline: terms.join_deadline,
+            lock_deadline: terms.lock_deadline,
+            resolve_deadline: terms.resolve_deadline,
+            fallback: terms.fallback,
+            dispute_window: terms.dispute_window,
+            disputed_until: 0,
+            outcome: Judgment::Pending,
+            belief_squares: 0,
+            doubt_squares: 0,
+        },
+        reserved_seats: first_seats.to_vec(),
+    };
+
+    let (counter, _) = find_counter_address(program_id, payer);
+
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*wager, false),
+        AccountMeta::new(counter, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    let seat_count = first_seats.len() as u8;
+    for seat in seat_addresses(program_id, wager, seat_count) {
+        accounts.push(AccountMeta::new(seat, false));
+    }
+
+    if let Some(mint) = terms.mint {
+        accounts.push(AccountMeta::new_readonly(mint.mint, false));
+        accounts.push(AccountMeta::new_readonly(mint.token_program, false));
+    }
+
+    instruction(program_id, &data, accounts)
+}
+
+// Creates the reserved seats from `start`, the wager's current seat_count,
+// one per authority
+pub fn init_seats_ix(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    wager: &Pubkey,
+    start: u8,
+    authorities: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new(*wager, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    // Seats created so far are checked for repeated authorities
+    for index in 0..start {
+        let (seat, _) = find_seat_address(program_id, wager, index);
+        accounts.push(AccountMeta::new_readonly(seat, false));
+    }
+
+    for offset in 0..authorities.len() as u8 {
+        let (seat, _) = find_seat_address(program_id, wager, start + offset);
+        accounts.push(AccountMeta::new(seat, false));
+    }
+
+    for authority in authorities {
+        accounts.push(AccountMeta::new_readonly(*authority, false));
+    }
+
+    let count = authorities.len() as u8;
+    instruction(program_id, &WagerInstruction::InitSeats { start, count }, accounts)
+}
+
+// `index` is the wager's current seat_count, the next seat to be taken.
+// The seats before it are passed so a signer cannot take a second one
+pub fn join_wager_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    index: u8,
+) -> Instruction {
+    let (seat, _) = find_seat_address(program_id, wager, index);
+
+    let mut accounts = vec![
+        AccountMeta::new(*signer, true),
+        AccountMeta::new(*wager, false),
+        AccountMeta::new(seat, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+    ];
+
+    for seat in seat_addresses(program_id, wager, index) {
+        accounts.push(AccountMeta::new_readonly(seat, false));
+    }
+
+    instruction(program_id, &WagerInstruction::JoinWager, accounts)
+}
+
+// `payer` gets the seat's rent back: the wager creator for reserved seats,
+// the seat holder for joined ones. Every seat is passed since later seats
+// move down one index. After the join deadline `authority` may be anyone
+// dropping a seat that was never staked
+pub fn leave_seat_ix(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wager: &Pubkey,
+    payer: &Pubkey,
+    seat_count: u8,
+    index: u8,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*wager, false),
+        AccountMeta::new(*payer, false),
+    ];
+
+    for seat in seat_addresses(program_id, wager, seat_count) {
+        accounts.push(AccountMeta::new(seat, false));
+    }
+
+    instruction(program_id, &WagerInstruction::LeaveSeat { index }, accounts)
+}
+
+pub fn deposit_ix(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wager: &Pubkey,
+    index: u8,
+    amount: u64,
+) -> Instruction {
+    let (vault, _) = find_vault_address(program_id, wager);
+    let (seat, _) = find_seat_address(program_id, wager, index);
+
+    instruction(
+        program_id,
+        &WagerInstruction::ProcessDeposit { index, amount },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*wager, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(seat, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+// Token stakes are drawn from `source`, a token account the authority owns
+pub fn deposit_token_ix(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wager: &Pubkey,
+    index: u8,
+    amount: u64,
+    source: &Pubkey,
+    mint: &TokenMint,
+) -> Instruction {
+    let (vault, _) = find_vault_address(program_id, wager);
+    let (seat, _) = find_seat_address(program_id, wager, index);
+
+    instruction(
+        program_id,
+        &WagerInstruction::ProcessDeposit { index, amount },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*wager, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(seat, false),
+            AccountMeta::new_readonly(mint.token_program, false),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(mint.mint, false),
+        ],
+    )
+}
+
+// Every seat is passed so that locked counterparties can be reset, and the
+// wager's count of locked seats with them
+pub fn update_belief_ix(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wager: &Pubkey,
+    seat_count: u8,
+    index: u8,
+    belief: u8,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*wager, false),
+    ];
+
+    for seat in seat_addresses(program_id, wager, seat_count) {
+        accounts.push(AccountMeta::new(seat, false));
+    }
+
+    instruction(program_id, &WagerInstruction::UpdateBelief { index, belief }, accounts)
+}
+
+// Every seat is read to detect the last lock; only the signer's seat is written
+pub fn lock_ix(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wager: &Pubkey,
+    seat_count: u8,
+    index: u8,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*wager, false),
+    ];
+
+    for (seat_index, seat) in seat_addresses(program_id, wager, seat_count).into_iter().enumerate() {
+        if seat_index == index as usize {
+            accounts.push(AccountMeta::new(seat, false));
+        } else {
+            accounts.push(AccountMeta::new_readonly(seat, false));
+        }
+    }
+
+    instruction(program_id, &WagerInstruction::LockStatus { index }, accounts)
+}
+
+// Every seat is read to detect consensus; only the signer's seat is written
+pub fn set_judgment_ix(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wager: &Pubkey,
+    seat_count: u8,
+    index: u8,
+    judgment: Judgment,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*wager, false),
+    ];
+
+    for (seat_index, seat) in seat_addresses(program_id, wager, seat_count).into_iter().enumerate() {
+        if seat_index == index as usize {
+            accounts.push(AccountMeta::new(seat, false));
+        } else {
+            accounts.push(AccountMeta::new_readonly(seat, false));
+        }
+    }
+
+    instruction(program_id, &WagerInstruction::SetJudgment { index, judgment }, accounts)
+}
+
+// Program moving stakes in and out of the vault
+fn transfer_program(mint: Option<&TokenMint>) -> Pubkey {
+    mint.map_or(system_program::ID, |mint| mint.token_program)
+}
+
+pub fn arbiter_judgment_ix(
+    program_id: &Pubkey,
+    arbiter: &Pubkey,
+    wager: &Pubkey,
+    judgment: Judgment,
+) -> Instruction {
+    instruction(
+        program_id,
+        &WagerInstruction::ArbiterJudgment { judgment },
+        vec![
+            AccountMeta::new_readonly(*arbiter, true),
+            AccountMeta::new(*wager, false),
+        ],
+    )
+}
+
+// Oracle fallbacks read the attestation of the oracle the wager names
+pub fn escalate_dispute_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    attestation: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*signer, true),
+        AccountMeta::new(*wager, false),
+    ];
+
+    if let Some(attestation) = attestation {
+        accounts.push(AccountMeta::new_readonly(*attestation, false));
+    }
+
+    instruction(program_id, &WagerInstruction::EscalateDispute, accounts)
+}
+
+// Seats `start..start + count` of a wager
+fn seat_batch_addresses(program_id: &Pubkey, wager: &Pubkey, start: u8, count: usize) -> Vec<Pubkey> {
+    (start..)
+        .take(count)
+        .map(|index| find_seat_address(program_id, wager, index).0)
+        .collect()
+}
+
+// Accounts shared by instructions that pay out of the vault: a batch of seats
+// from `start`, then one recipient per seat in the same order, then the mint
+// of a token wager
+fn vault_payout_accounts(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    mint: Option<&TokenMint>,
+    start: u8,
+    recipients: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let (vault, _) = find_vault_address(program_id, wager);
+    let transfer_program = transfer_program(mint);
+
+    let mut accounts = vec![
+        AccountMeta::new(*signer, true),
+        AccountMeta::new(*wager, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(transfer_program, false),
+    ];
+
+    for seat in seat_batch_addresses(program_id, wager, start, recipients.len()) {
+        accounts.push(AccountMeta::new_readonly(seat, false));
+    }
+
+    for recipient in recipients {
+        accounts.push(AccountMeta::new(*recipient, false));
+    }
+
+    if let Some(mint) = mint {
+        accounts.push(AccountMeta::new_readonly(mint.mint, false));
+    }
+
+    accounts
+}
+
+// Seat authorities must be given in seat order; they receive the payouts
+pub fn render_payouts_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    seat_authorities: &[Pubkey],
+) -> Instruction {
+    render_batched_payouts_ix(program_id, signer, wager, None, 0, seat_authorities)
+}
+
+// Token accounts owned by each seat authority, in seat order
+pub fn render_token_payouts_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    mint: &TokenMint,
+    recipients: &[Pubkey],
+) -> Instruction {
+    render_batched_payouts_ix(program_id, signer, wager, Some(mint), 0, recipients)
+}
+
+// Pays seats `start..start + recipients.len()`, where `start` is the wager's
+// cursor: settlements too large for one transaction go out in batches, and
+// the wager counts as settled once the last seat is paid
+pub fn render_batched_payouts_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    mint: Option<&TokenMint>,
+    start: u8,
+    recipients: &[Pubkey],
+) -> Instruction {
+    let accounts = vault_payout_accounts(program_id, signer, wager, mint, start, recipients);
+    instruction(program_id, &WagerInstruction::RenderPayouts, accounts)
+}
+
+// Seat authorities must be given in seat order; staked seats are refunded
+pub fn cancel_wager_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    seat_authorities: &[Pubkey],
+) -> Instruction {
+    cancel_batched_wager_ix(program_id, signer, wager, None, 0, seat_authorities)
+}
+
+// Token accounts owned by each seat authority, in seat order
+pub fn cancel_token_wager_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    mint: &TokenMint,
+    recipients: &[Pubkey],
+) -> Instruction {
+    cancel_batched_wager_ix(program_id, signer, wager, Some(mint), 0, recipients)
+}
+
+// Refunds seats `start..start + recipients.len()`, where `start` is the
+// wager's cursor. The first batch decides whether the signer may cancel, so a
+// seat holder's own seat has to be in it; later batches only carry it out
+pub fn cancel_batched_wager_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    mint: Option<&TokenMint>,
+    start: u8,
+    recipients: &[Pubkey],
+) -> Instruction {
+    let accounts = vault_payout_accounts(program_id, signer, wager, mint, start, recipients);
+    instruction(program_id, &WagerInstruction::CancelWager, accounts)
+}
+
+// Rent recipients for every seat of a wager closed in one go: the creator
+// paid for the reserved seats, the holders of the rest for their own
+fn all_rent_recipients(creator: &Pubkey, seat_count: u8, joined_authorities: &[Pubkey]) -> Vec<Pubkey> {
+    let reserved_count = (seat_count as usize).saturating_sub(joined_authorities.len());
+
+    let mut recipients = vec![*creator; reserved_count];
+    recipients.extend_from_slice(joined_authorities);
+    recipients
+}
+
+fn close_accounts(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    creator: &Pubkey,
+    token: Option<(&TokenMint, &Pubkey)>,
+    start: u8,
+    rent_recipients: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let (vault, _) = find_vault_address(program_id, wager);
+    let mint = token.map(|(mint, _)| mint);
+    let transfer_program = transfer_program(mint);
+
+    let mut accounts = vec![
+        AccountMeta::new(*signer, true),
+        AccountMeta::new(*wager, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new(*creator, false),
+        AccountMeta::new_readonly(transfer_program, false),
+    ];
+
+    for seat in seat_batch_addresses(program_id, wager, start, rent_recipients.len()) {
+        accounts.push(AccountMeta::new(seat, false));
+    }
+
+    for recipient in rent_recipients {
+        accounts.push(AccountMeta::new(*recipient, false));
+    }
+
+    // Writable so fees withheld in a Token-2022 vault can be harvested into it;
+    // tokens still in the vault are swept to the creator's token account
+    if let Some((mint, creator_token_account)) = token {
+        accounts.push(AccountMeta::new(mint.mint, false));
+        accounts.push(AccountMeta::new(*creator_token_account, false));
+    }
+
+    accounts
+}
+
+// Rent of the wager, its vault and its reserved seats goes to the recorded creator;
+// joined seats return theirs to `joined_authorities`, the holders of the seats
+// past the reserved ones, in seat order
+pub fn close_wager_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    creator: &Pubkey,
+    seat_count: u8,
+    joined_authorities: &[Pubkey],
+) -> Instruction {
+    let recipients = all_rent_recipients(creator, seat_count, joined_authorities);
+    close_batched_wager_ix(program_id, signer, wager, creator, None, 0, &recipients)
+}
+
+// Leftover vault tokens are swept to `creator_token_account`. One argument per
+// account group, like the lamport variant, so they are not bundled
+#[allow(clippy::too_many_arguments)]
+pub fn close_token_wager_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    creator: &Pubkey,
+    mint: &TokenMint,
+    creator_token_account: &Pubkey,
+    seat_count: u8,
+    joined_authorities: &[Pubkey],
+) -> Instruction {
+    let token = Some((mint, creator_token_account));
+    let recipients = all_rent_recipients(creator, seat_count, joined_authorities);
+    close_batched_wager_ix(program_id, signer, wager, creator, token, 0, &recipients)
+}
+
+// Closes the wager's last seats, `start..start + rent_recipients.len()`, with
+// each seat's rent going to the matching recipient: the creator for reserved
+// seats, the holder for joined ones. The batch that reaches seat 0 also
+// closes the vault and the wager; token wagers pass the mint and the
+// creator's token account with every batch
+pub fn close_batched_wager_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    creator: &Pubkey,
+    token: Option<(&TokenMint, &Pubkey)>,
+    start: u8,
+    rent_recipients: &[Pubkey],
+) -> Instruction {
+    let accounts = close_accounts(program_id, signer, wager, creator, token, start, rent_recipients);
+    instruction(program_id, &WagerInstruction::CloseWager, accounts)
+}
+
+// The oracle signs and pays for the attestation account
+pub fn publish_attestation_ix(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    contract: &Pubkey,
+    outcome: Judgment,
+) -> Instruction {
+    let (attestation, _) = find_attestation_address(program_id, oracle, contract);
+
+    instruction(
+        program_id,
+        &WagerInstruction::PublishAttestation { contract: *contract, outcome },
+        vec![
+            AccountMeta::new(*oracle, true),
+            AccountMeta::new(attestation, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+// Anyone may apply the attestation of the oracle the wager names
+pub fn resolve_from_oracle_ix(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    wager: &Pubkey,
+    oracle: &Pubkey,
+    contract: &Pubkey,
+) -> Instruction {
+    let (attestation, _) = find_attestation_address(program_id, oracle, contract);
+
+    instruction(
+        program_id,
+        &WagerInstruction::ResolveFromOracle,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*wager, false),
+            AccountMeta::new_readonly(attestation, false),
+        ],
+    )
+}
+
+// Address and data of an attestation as the program would publish it, for
+// preloading a mock oracle's outcome into solana-program-test with add_account
+pub fn mock_attestation(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    contract: &Pubkey,
+    outcome: Judgment,
+    timestamp: i64,
+) -> (Pubkey, Vec<u8>) {
+    let (address, bump) = find_attestation_address(program_id, oracle, contract);
+
+    let attestation = Attestation {
+        discriminator: Attestation::DISCRIMINATOR,
+        version: Attestation::VERSION,
+        oracle: *oracle,
+        contract: *contract,
+        bump,
+        outcome,
+        timestamp,
+    };
+
+    (address, to_vec(&attestation).expect("attestation serializes"))
+}
+
+// Upgrades a v0 seat account; the payer covers any extra rent
+pub fn migrate_account_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    account: &Pubkey,
+) -> Instruction {
+    instruction(
+        program_id,
+        &WagerInstruction::MigrateAccount,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+    )
+}
+
+// Upgrades a v0 wager account; the holder of its first seat is recorded as
+// the creator and gets the wager's rent back on close
+pub fn migrate_wager_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    wager: &Pubkey,
+) -> Instruction {
+    let mut instruction = migrate_account_ix(program_id, payer, wager);
+    let (first_seat, _) = find_seat_address(program_id, wager, 0);
+    instruction.accounts.push(AccountMeta::new_readonly(first_seat, false));
+    instruction
+}
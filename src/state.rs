@@ -1,30 +1,199 @@
 // state.rs
 
+use crate::error::WagerError;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use solana_program::{
+    program_error::ProgramError,
     pubkey::Pubkey,
     // system_program::ID,
 };
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+// Every account starts with an 8-byte discriminator followed by a version byte
+pub const HEADER_SPACE: usize = 8 + 1;
+
+// Rejects accounts of another type or layout before they are deserialized
+fn check_header(data: &[u8], discriminator: &[u8; 8], version: u8) -> Result<(), ProgramError> {
+    if data.get(..8) != Some(&discriminator[..]) {
+        return Err(WagerError::InvalidAccountType.into());
+    }
+
+    if data.get(8) != Some(&version) {
+        return Err(WagerError::UnsupportedAccountVersion.into());
+    }
+
+    Ok(())
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
 pub struct Wager {
+    pub discriminator: [u8; 8],     // 8 bytes
+    pub version: u8,                // 1 byte
+    pub phase: Phase,               // 1 byte
+    pub creator: Pubkey,            // 32 bytes, paid rent and receives it back on close
     pub contract: Pubkey,           // 32 bytes
+    pub mint: Pubkey,               // 32 bytes, default for lamport stakes
+    pub oracle: Pubkey,             // 32 bytes, default when only seats judge
+    pub arbiter: Pubkey,            // 32 bytes, default when nobody breaks ties
     pub vault: Pubkey,              // 32 bytes
     pub vault_bump: u8,             // 1 byte
-    pub seat_count: u8,             // 1 byte
+    pub seat_count: u8,             // 1 byte, seats created so far
+    pub reserved_count: u8,         // 1 byte, seats bound to an authority up front
+    pub staked_count: u8,           // 1 byte, seats that paid their stake
+    pub locked_count: u8,           // 1 byte, seats locked into the current terms
     pub capacity: u8,               // 1 byte
     pub stake: u64,                 // 8 bytes
+    pub pot: u64,                   // 8 bytes, stakes the vault actually received
+    pub paid: u64,                  // 8 bytes, paid out of the pot by the batches so far
+    pub cursor: u8,                 // 1 byte, seats paid by a cancel or settlement so far
+    pub join_deadline: i64,         // 8 bytes
+    pub lock_deadline: i64,         // 8 bytes
+    pub resolve_deadline: i64,      // 8 bytes
+    pub fallback: Fallback,         // 1 byte, decides disputes nobody settled
+    pub dispute_window: i64,        // 8 bytes, seconds seats get to amend
+    pub disputed_until: i64,        // 8 bytes, 0 unless a dispute was opened
+    pub outcome: Judgment,          // 1 byte
+    pub belief_squares: u32,        // 4 bytes, sum of belief² once every seat locked
+    pub doubt_squares: u32,         // 4 bytes, sum of (100 - belief)² once every seat locked
 }
 
 impl Wager {
-    pub const SPACE: usize = 32 + 32 + 1 + 1 + 1 + 8;
+    pub const DISCRIMINATOR: [u8; 8] = *b"wager\0\0\0";
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = HEADER_SPACE + 1 + 32 + 32 + 32 + 32 + 32 + 32 + 1 + 1 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 1 + 8 + 8 + 1 + 4 + 4;
+
+    // Phase sits right after the header so indexers can memcmp on it
+    pub const PHASE_OFFSET: usize = HEADER_SPACE;
+
+    pub fn load(data: &[u8]) -> Result<Self, ProgramError> {
+        check_header(data, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(Self::try_from_slice(data)?)
+    }
+
+    // Stakes are native lamports unless the wager names a token mint
+    pub fn is_native(&self) -> bool {
+        self.mint == Pubkey::default()
+    }
+
+    // Wagers naming an oracle can be resolved by its attestation alone
+    pub fn has_oracle(&self) -> bool {
+        self.oracle != Pubkey::default()
+    }
+
+    // Wagers naming an arbiter let it settle disagreeing or overdue judgments
+    pub fn has_arbiter(&self) -> bool {
+        self.arbiter != Pubkey::default()
+    }
+
+    // Reserved seats may be created over several InitSeats calls; the wager
+    // stays in setup until the last of them exists
+    pub fn seats_initialized(&self) -> bool {
+        self.seat_count >= self.reserved_count
+    }
+
+    // Cancels and settlements pay seats in batches, in seat order; the wager
+    // can only close once the last batch went out
+    pub fn payouts_finished(&self) -> bool {
+        self.cursor >= self.seat_count
+    }
+
+    // Rejects the instruction unless the wager is in one of `phases`
+    pub fn expect_phase(&self, phases: &[Phase]) -> Result<(), ProgramError> {
+        if !phases.contains(&self.phase) {
+            return Err(WagerError::InvalidPhase.into());
+        }
+        Ok(())
+    }
+
+    // Deadlines must be in the future and close in join, lock, resolve order
+    pub fn check_deadlines(&self, now: i64) -> Result<(), ProgramError> {
+        if now >= self.join_deadline
+            || self.join_deadline > self.lock_deadline
+            || self.lock_deadline > self.resolve_deadline
+        {
+            return Err(WagerError::InvalidDeadlines.into());
+        }
+        Ok(())
+    }
+
+    // Conflicting judgments get a window for amendments before the fallback decides
+    pub fn open_dispute(&mut self, now: i64) -> Result<(), ProgramError> {
+        self.advance(Phase::Judging)?;
+        self.advance(Phase::Disputed)?;
+        self.disputed_until = now.saturating_add(self.dispute_window);
+        Ok(())
+    }
+
+    // Disputes only open before the resolve deadline, so by this point every
+    // window has closed and the fallback had its turn
+    pub fn settle_deadline(&self) -> i64 {
+        self.resolve_deadline.saturating_add(self.dispute_window)
+    }
+
+    pub fn dispute_expired(&self, now: i64) -> bool {
+        self.phase == Phase::Disputed && now > self.disputed_until
+    }
+
+    // Every phase change goes through here; staying in place is always allowed
+    pub fn advance(&mut self, next: Phase) -> Result<(), ProgramError> {
+        if self.phase != next && !self.phase.can_advance_to(next) {
+            return Err(WagerError::InvalidPhase.into());
+        }
+        self.phase = next;
+        Ok(())
+    }
+}
+
+// Lifecycle of a wager as a whole, tracked separately from each seat's Status
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    Created,
+    Funding,
+    Locked,
+    Judging,
+    Settled,
+    Cancelled,
+    Disputed,
+}
+
+impl Phase {
+    pub fn can_advance_to(self, next: Phase) -> bool {
+        matches!(
+            (self, next),
+            (Phase::Created, Phase::Funding)
+                | (Phase::Created, Phase::Cancelled)
+                | (Phase::Funding, Phase::Locked)
+                | (Phase::Funding, Phase::Cancelled)
+                | (Phase::Locked, Phase::Judging)
+                | (Phase::Judging, Phase::Settled)
+                | (Phase::Judging, Phase::Disputed)
+                | (Phase::Disputed, Phase::Judging)
+        )
+    }
+}
+
+// Who decides a dispute once its window closes without consensus
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fallback {
+    Push,
+    Arbiter,
+    Oracle,
 }
 
+// Highest belief a seat may record
+pub const MAX_BELIEF: u8 = 100;
+
+// Belief of a seat that has not recorded a forecast yet
+pub const UNSET_BELIEF: u8 = 255;
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Seat {
+    pub discriminator: [u8; 8],     // 8 bytes
+    pub version: u8,                // 1 byte
     pub wager: Pubkey,              // 32 bytes
     pub authority: Pubkey,          // 32 bytes
+    pub bump: u8,                   // 1 byte
     pub belief: u8,                 // 1 byte
     pub status: Status,             // 1 byte
     pub judgment: Judgment,         // 1 byte
@@ -32,13 +201,23 @@ pub struct Seat {
 }
 
 impl Seat {
-    pub const SPACE: usize = 32 + 32 + 1 + 1 + 1 +8;
+    pub const DISCRIMINATOR: [u8; 8] = *b"seat\0\0\0\0";
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = HEADER_SPACE + 32 + 32 + 1 + 1 + 1 + 1 + 8;
+
+    pub fn load(data: &[u8]) -> Result<Self, ProgramError> {
+        check_header(data, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(Self::try_from_slice(data)?)
+    }
 
-    pub fn take(wager: Pubkey, authority: Pubkey, timestamp: i64) -> Self {
+    pub fn take(wager: Pubkey, authority: Pubkey, bump: u8, timestamp: i64) -> Self {
         Seat {
-            wager: wager,
-            authority: authority,
-            belief: 255,
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            wager,
+            authority,
+            bump,
+            belief: UNSET_BELIEF,
             status: Status::Open,
             judgment: Judgment::Pending,
             last_change_at: timestamp
@@ -46,8 +225,180 @@ impl Seat {
     }
 }
 
+// Outcome an oracle published for a contract, stored at the attestation PDA
+// of that oracle and contract so only the oracle itself can have written it
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct Attestation {
+    pub discriminator: [u8; 8],     // 8 bytes
+    pub version: u8,                // 1 byte
+    pub oracle: Pubkey,             // 32 bytes, signed the publication
+    pub contract: Pubkey,           // 32 bytes
+    pub bump: u8,                   // 1 byte
+    pub outcome: Judgment,          // 1 byte
+    pub timestamp: i64,             // 8 bytes
+}
+
+impl Attestation {
+    pub const DISCRIMINATOR: [u8; 8] = *b"attest\0\0";
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = HEADER_SPACE + 32 + 32 + 1 + 1 + 8;
+
+    pub fn load(data: &[u8]) -> Result<Self, ProgramError> {
+        check_header(data, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(Self::try_from_slice(data)?)
+    }
+}
+
+// Per-creator counter at [b"counter", creator]; the creator's wagers live at
+// [b"wager", creator, nonce] for every nonce below wager_count
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct WagerCounter {
+    pub discriminator: [u8; 8],     // 8 bytes
+    pub version: u8,                // 1 byte
+    pub creator: Pubkey,            // 32 bytes
+    pub bump: u8,                   // 1 byte
+    pub wager_count: u64,           // 8 bytes, nonce of the next wager
+}
+
+impl WagerCounter {
+    pub const DISCRIMINATOR: [u8; 8] = *b"counter\0";
+    pub const VERSION: u8 = 1;
+    pub const SPACE: usize = HEADER_SPACE + 32 + 1 + 8;
+
+    pub fn load(data: &[u8]) -> Result<Self, ProgramError> {
+        check_header(data, &Self::DISCRIMINATOR, Self::VERSION)?;
+        Ok(Self::try_from_slice(data)?)
+    }
+
+    pub fn new(creator: Pubkey, bump: u8) -> Self {
+        WagerCounter {
+            discriminator: Self::DISCRIMINATOR,
+            version: Self::VERSION,
+            creator,
+            bump,
+            wager_count: 0,
+        }
+    }
+}
+
+// Layouts written before accounts carried a discriminator and version,
+// kept so MigrateAccount can upgrade them in place
+#[derive(BorshDeserialize, Debug)]
+pub struct WagerV0 {
+    pub contract: Pubkey,
+    pub vault: Pubkey,
+    pub vault_bump: u8,
+    pub seat_count: u8,
+    pub capacity: u8,
+    pub stake: u64,
+}
+
+impl WagerV0 {
+    pub const SPACE: usize = 32 + 32 + 1 + 1 + 1 + 8;
+}
+
+impl From<WagerV0> for Wager {
+    fn from(legacy: WagerV0) -> Self {
+        Wager {
+            discriminator: Wager::DISCRIMINATOR,
+            version: Wager::VERSION,
+            // v0 could not pay out, so every v0 wager is still funding
+            phase: Phase::Funding,
+            // v0 never recorded its creator; MigrateAccount names one
+            creator: Pubkey::default(),
+            contract: legacy.contract,
+            mint: Pubkey::default(),
+            oracle: Pubkey::default(),
+            arbiter: Pubkey::default(),
+            vault: legacy.vault,
+            vault_bump: legacy.vault_bump,
+            seat_count: legacy.seat_count,
+            reserved_count: legacy.seat_count,
+            staked_count: legacy.seat_count,
+            // Locks are made again, see the seat conversion below
+            locked_count: 0,
+            capacity: legacy.capacity,
+            stake: legacy.stake,
+            // v0 only took lamports, which arrive in full and pay out by stake
+            pot: legacy.stake.saturating_mul(legacy.seat_count as u64),
+            paid: 0,
+            cursor: 0,
+            // v0 wagers were created without deadlines and stay open-ended
+            join_deadline: i64::MAX,
+            lock_deadline: i64::MAX,
+            resolve_deadline: i64::MAX,
+            // v0 predates disputes, so conflicts push as soon as they escalate
+            fallback: Fallback::Push,
+            dispute_window: 0,
+            disputed_until: 0,
+            outcome: Judgment::Pending,
+            belief_squares: 0,
+            doubt_squares: 0,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, Debug)]
+pub struct SeatV0 {
+    pub wager: Pubkey,
+    pub authority: Pubkey,
+    pub belief: u8,
+    pub status: Status,
+    pub judgment: Judgment,
+    pub last_change_at: i64,
+}
+
+impl SeatV0 {
+    pub const SPACE: usize = 32 + 32 + 1 + 1 + 1 + 8;
+}
+
+impl From<SeatV0> for Seat {
+    fn from(legacy: SeatV0) -> Self {
+        Seat {
+            discriminator: Seat::DISCRIMINATOR,
+            version: Seat::VERSION,
+            wager: legacy.wager,
+            authority: legacy.authority,
+            // v0 never stored the bump; MigrateAccount derives it
+            bump: 0,
+            belief: legacy.belief,
+            // Migrated wagers restart in Funding, so locks are made again
+            status: match legacy.status {
+                Status::Locked => Status::Staked,
+                status => status,
+            },
+            judgment: legacy.judgment,
+            last_change_at: legacy.last_change_at,
+        }
+    }
+}
+
+// True once every seat has agreed to the current terms
+pub fn all_locked(seats: &[Seat]) -> bool {
+    !seats.is_empty() && seats.iter().all(|seat| seat.status == Status::Locked)
+}
+
+// True once every seat has recorded a judgment
+pub fn all_judged(seats: &[Seat]) -> bool {
+    seats.iter().all(|seat| seat.judgment != Judgment::Pending)
+}
+
+// Outcome every seat agrees on, if they all agree on one
+pub fn consensus(seats: &[Seat]) -> Option<Judgment> {
+    let first = seats.first()?.judgment;
+
+    if first == Judgment::Pending {
+        return None;
+    }
+
+    seats
+        .iter()
+        .all(|seat| seat.judgment == first)
+        .then_some(first)
+}
+
 // Game states for a given seat or participant
-#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Debug)]
 pub enum Status {
     Open,
     Staked,
@@ -55,7 +406,7 @@ pub enum Status {
 }
 
 // Outcome, decided by participants
-#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Debug)]
 pub enum Judgment {
     Pending,
     Landed,
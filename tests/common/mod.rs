@@ -0,0 +1,455 @@
+// Helpers shared by the integration tests; every test binary compiles its
+// own copy and only uses some of them
+#![allow(dead_code)]
+
+use solana_god::process_instruction;
+use solana_god::error::WagerError;
+use solana_god::sdk::{
+    find_counter_address,
+    find_wager_address,
+    find_seat_address,
+    mock_attestation,
+    create_wager_ix,
+    deposit_ix,
+    update_belief_ix,
+    lock_ix,
+    set_judgment_ix,
+    deposit_token_ix,
+    WagerTerms,
+    TokenMint,
+};
+use solana_god::state::{Wager, WagerCounter, Seat, Phase, Fallback, Judgment};
+
+use solana_program::{
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+};
+
+use solana_system_interface::instruction as system_instruction;
+
+use solana_program_test::*;
+
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeAmount,
+    BaseStateWithExtensions,
+    ExtensionType,
+    StateWithExtensions,
+};
+use spl_token_2022::state::{Account as TokenAccount, Mint};
+
+use solana_sdk::{
+    account::Account,
+    packet::PACKET_DATA_SIZE,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+    signer::keypair::Keypair
+};
+
+pub const STAKE: u64 = 100_000_000; // 0.1 SOL
+pub const SEED: u64 = 1_000_000_000; // 1 SOL
+pub const TOKEN_STAKE: u64 = 1_000_000; // 1 token at 6 decimals
+pub const MINTED: u64 = 10_000_000;
+
+pub fn program_test(program_id: Pubkey) -> ProgramTest {
+    ProgramTest::new("solana_god", program_id, processor!(process_instruction))
+}
+
+// Writes an oracle's attestation straight into the test ledger, with any
+// timestamp, and returns its address
+pub fn preload_attestation(
+    program_test: &mut ProgramTest,
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    contract: &Pubkey,
+    outcome: Judgment,
+    timestamp: i64,
+) -> Pubkey {
+    let (address, data) = mock_attestation(program_id, oracle, contract, outcome, timestamp);
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: *program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    address
+}
+
+// A running test validator with funded wallets to hold seats
+pub struct Setup {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub wallets: Vec<Keypair>,
+    pub now: i64,
+}
+
+impl Setup {
+    pub async fn new(wallet_count: usize) -> Self {
+        let program_id = Pubkey::new_unique();
+        Self::start(program_test(program_id), program_id, wallet_count).await
+    }
+
+    // Starts `program_test`, which may carry preloaded accounts, and funds
+    // `wallet_count` wallets from the payer
+    pub async fn start(program_test: ProgramTest, program_id: Pubkey, wallet_count: usize) -> Self {
+        let context = program_test.start_with_context().await;
+        let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+        let wallets: Vec<Keypair> = (0..wallet_count).map(|_| Keypair::new()).collect();
+
+        let mut setup = Setup { context, program_id, wallets, now };
+        let keys = setup.authorities();
+        setup.fund(&keys).await;
+        setup
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub fn wallet(&self, index: usize) -> Keypair {
+        self.wallets[index].insecure_clone()
+    }
+
+    pub fn authorities(&self) -> Vec<Pubkey> {
+        self.wallets.iter().map(|wallet| wallet.pubkey()).collect()
+    }
+
+    pub async fn fund(&mut self, keys: &[Pubkey]) {
+        let payer = self.payer();
+        let transfers: Vec<Instruction> = keys
+            .iter()
+            .map(|key| system_instruction::transfer(&payer, key, SEED))
+            .collect();
+        self.send(&transfers, &[]).await.unwrap();
+    }
+
+    // Lamport terms with a seat for every wallet and an hour per window
+    pub fn terms(&self) -> WagerTerms {
+        WagerTerms {
+            contract: Pubkey::new_unique(),
+            mint: None,
+            oracle: None,
+            arbiter: None,
+            capacity: self.wallets.len() as u8,
+            stake: STAKE,
+            join_deadline: self.now + 3_600,
+            lock_deadline: self.now + 7_200,
+            resolve_deadline: self.now + 86_400,
+            fallback: Fallback::Push,
+            dispute_window: 3_600,
+        }
+    }
+
+    // Address of the payer's next wager, read from their counter
+    pub async fn next_wager(&mut self) -> Pubkey {
+        let creator = self.payer();
+        let (counter, _) = find_counter_address(&self.program_id, &creator);
+        let nonce = match self.context.banks_client.get_account(counter).await.unwrap() {
+            Some(account) => WagerCounter::load(&account.data).unwrap().wager_count,
+            None => 0,
+        };
+        find_wager_address(&self.program_id, &creator, nonce).0
+    }
+
+    // Creates the payer's next wager with `reserved_seats`
+    pub async fn create(&mut self, terms: &WagerTerms, reserved_seats: &[Pubkey]) -> Result<Pubkey, BanksClientError> {
+        let payer = self.payer();
+        let wager = self.next_wager().await;
+        let instruction = create_wager_ix(&self.program_id, &payer, &wager, terms, reserved_seats);
+        self.send(&[instruction], &[]).await?;
+
+        Ok(wager)
+    }
+
+    // Creates the payer's next wager with a reserved seat for every wallet
+    pub async fn create_for_all(&mut self, terms: &WagerTerms) -> Pubkey {
+        let authorities = self.authorities();
+        self.create(terms, &authorities).await.unwrap()
+    }
+
+    pub async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        send(&mut self.context, instructions, signers).await
+    }
+
+    // Sends `instructions` signed by wallet `index`
+    pub async fn play(&mut self, index: usize, instructions: &[Instruction]) -> Result<(), BanksClientError> {
+        let wallet = self.wallet(index);
+        self.send(instructions, &[&wallet]).await
+    }
+
+    // Stakes seat `index` with wallet `index` and records `belief`
+    pub async fn stake(&mut self, wager: &Pubkey, index: u8, belief: u8) {
+        let authority = self.wallets[index as usize].pubkey();
+        let seat_count = self.wager_state(wager).await.seat_count;
+        let instructions = [
+            deposit_ix(&self.program_id, &authority, wager, index, STAKE),
+            update_belief_ix(&self.program_id, &authority, wager, seat_count, index, belief),
+        ];
+        self.play(index as usize, &instructions).await.unwrap();
+    }
+
+    pub async fn lock(&mut self, wager: &Pubkey, index: u8) -> Result<(), BanksClientError> {
+        let authority = self.wallets[index as usize].pubkey();
+        let seat_count = self.wager_state(wager).await.seat_count;
+        let instruction = lock_ix(&self.program_id, &authority, wager, seat_count, index);
+        self.play(index as usize, &[instruction]).await
+    }
+
+    // Stakes and locks every seat in turn with the given beliefs
+    pub async fn lock_all(&mut self, wager: &Pubkey, beliefs: &[u8]) {
+        for (index, belief) in beliefs.iter().enumerate() {
+            self.stake(wager, index as u8, *belief).await;
+        }
+
+        for index in 0..beliefs.len() {
+            self.lock(wager, index as u8).await.unwrap();
+        }
+    }
+
+    pub async fn judge(&mut self, wager: &Pubkey, index: u8, judgment: Judgment) -> Result<(), BanksClientError> {
+        let authority = self.wallets[index as usize].pubkey();
+        let seat_count = self.wager_state(wager).await.seat_count;
+        let instruction = set_judgment_ix(&self.program_id, &authority, wager, seat_count, index, judgment);
+        self.play(index as usize, &[instruction]).await
+    }
+
+    pub async fn wager_state(&mut self, wager: &Pubkey) -> Wager {
+        let account = self.context.banks_client.get_account(*wager).await.unwrap().unwrap();
+        Wager::load(&account.data).unwrap()
+    }
+
+    pub async fn seat(&mut self, wager: &Pubkey, index: u8) -> Option<Seat> {
+        let (seat, _) = find_seat_address(&self.program_id, wager, index);
+        let account = self.context.banks_client.get_account(seat).await.unwrap()?;
+        Some(Seat::load(&account.data).unwrap())
+    }
+
+    pub async fn exists(&mut self, key: &Pubkey) -> bool {
+        self.context.banks_client.get_account(*key).await.unwrap().is_some()
+    }
+
+    pub async fn balance(&mut self, key: &Pubkey) -> u64 {
+        self.context.banks_client.get_balance(*key).await.unwrap()
+    }
+
+    pub async fn balances(&mut self, keys: &[Pubkey]) -> Vec<u64> {
+        let mut balances = Vec::new();
+        for key in keys {
+            balances.push(self.balance(key).await);
+        }
+        balances
+    }
+
+    pub async fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock = self.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+    }
+}
+
+// A two-seat wager staked in a local mint, with a funded token account for
+// each wallet and one for the creator
+pub struct TokenSetup {
+    pub setup: Setup,
+    pub mint: TokenMint,
+    pub token_accounts: Vec<Pubkey>,
+    pub creator_account: Pubkey,
+    pub wager: Pubkey,
+}
+
+impl TokenSetup {
+    // Creates a mint owned by `token_program` carrying `extensions`, which
+    // `configure_mint(mint, authority)` initializes before the mint itself
+    pub async fn new(
+        token_program: Pubkey,
+        extensions: &[ExtensionType],
+        configure_mint: impl Fn(&Pubkey, &Pubkey) -> Vec<Instruction>,
+    ) -> Self {
+        let mut setup = Setup::new(2).await;
+        let payer = setup.payer();
+        let rent = setup.context.banks_client.get_rent().await.unwrap();
+
+        let mint = Keypair::new();
+        let mint_space = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+
+        let mut instructions = vec![
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(mint_space),
+                mint_space as u64,
+                &token_program,
+            ),
+        ];
+        instructions.extend(configure_mint(&mint.pubkey(), &payer));
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(&token_program, &mint.pubkey(), &payer, None, 6).unwrap(),
+        );
+        setup.send(&instructions, &[&mint]).await.unwrap();
+
+        // Token accounts need whatever extensions the mint requires of them
+        let account_extensions = ExtensionType::get_required_init_account_extensions(extensions);
+        let account_space = ExtensionType::try_calculate_account_len::<TokenAccount>(&account_extensions).unwrap();
+
+        // The creator gets an account too, for whatever is left in the vault on close
+        let mut owners = setup.authorities();
+        owners.push(payer);
+
+        let mut token_accounts = Vec::new();
+
+        for owner in owners {
+            let token_account = Keypair::new();
+            setup.send(
+                &[
+                    system_instruction::create_account(
+                        &payer,
+                        &token_account.pubkey(),
+                        rent.minimum_balance(account_space),
+                        account_space as u64,
+                        &token_program,
+                    ),
+                    spl_token_2022::instruction::initialize_account3(
+                        &token_program,
+                        &token_account.pubkey(),
+                        &mint.pubkey(),
+                        &owner,
+                    ).unwrap(),
+                    spl_token_2022::instruction::mint_to(
+                        &token_program,
+                        &mint.pubkey(),
+                        &token_account.pubkey(),
+                        &payer,
+                        &[],
+                        MINTED,
+                    ).unwrap(),
+                ],
+                &[&token_account],
+            )
+            .await
+            .unwrap();
+
+            token_accounts.push(token_account.pubkey());
+        }
+
+        let creator_account = token_accounts.pop().unwrap();
+
+        let mint = TokenMint { mint: mint.pubkey(), token_program };
+
+        let mut terms = setup.terms();
+        terms.mint = Some(mint);
+        terms.stake = TOKEN_STAKE;
+        let wager = setup.create_for_all(&terms).await;
+
+        TokenSetup { setup, mint, token_accounts, creator_account, wager }
+    }
+
+    // Stakes seat `index` from its wallet's token account and records `belief`
+    pub async fn deposit(&mut self, index: u8, belief: u8) {
+        let authority = self.setup.wallets[index as usize].pubkey();
+        let source = self.token_accounts[index as usize];
+        let instructions = [
+            deposit_token_ix(&self.setup.program_id, &authority, &self.wager, index, TOKEN_STAKE, &source, &self.mint),
+            update_belief_ix(&self.setup.program_id, &authority, &self.wager, 2, index, belief),
+        ];
+        self.setup.play(index as usize, &instructions).await.unwrap();
+    }
+
+    pub async fn data(&mut self, key: Pubkey) -> Vec<u8> {
+        self.setup.context.banks_client.get_account(key).await.unwrap().unwrap().data
+    }
+
+    pub async fn token_account(&mut self, key: Pubkey) -> TokenAccount {
+        let data = self.data(key).await;
+        StateWithExtensions::<TokenAccount>::unpack(&data).unwrap().base
+    }
+
+    // Transfer fees held back in a token account; none without the extension
+    pub async fn withheld(&mut self, key: Pubkey) -> u64 {
+        let data = self.data(key).await;
+        let account = StateWithExtensions::<TokenAccount>::unpack(&data).unwrap();
+        account
+            .get_extension::<TransferFeeAmount>()
+            .map(|fee| u64::from(fee.withheld_amount))
+            .unwrap_or(0)
+    }
+
+    pub async fn pot(&mut self) -> u64 {
+        let wager = self.wager;
+        self.setup.wager_state(&wager).await.pot
+    }
+}
+
+// Signs with the payer plus `signers` against a fresh blockhash, so repeated
+// identical transactions are never deduplicated. The banks client accepts
+// oversized transactions that a validator would drop, so size is checked here
+pub async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let payer = context.payer.insecure_clone();
+    let mut all_signers = vec![&payer];
+    all_signers.extend_from_slice(signers);
+
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    transaction.sign(&all_signers, recent_blockhash);
+
+    let size = 1 + 64 * transaction.signatures.len() + transaction.message.serialize().len();
+    assert!(size <= PACKET_DATA_SIZE, "transaction is {size} bytes, over the {PACKET_DATA_SIZE} byte limit");
+
+    context.banks_client.process_transaction(transaction).await
+}
+
+pub fn instruction_error(result: Result<(), BanksClientError>) -> Option<InstructionError> {
+    match result {
+        Err(BanksClientError::TransactionError(
+            TransactionError::InstructionError(_, error)
+        )) => Some(error),
+        _ => None,
+    }
+}
+
+// Decodes the program error a failed transaction returned, if any
+pub fn wager_error(result: Result<(), BanksClientError>) -> Option<WagerError> {
+    instruction_error(result).and_then(|error| WagerError::from_instruction_error(&error))
+}
+
+pub async fn wager_phase(banks_client: &mut BanksClient, wager_key: Pubkey) -> Phase {
+    let account = banks_client.get_account(wager_key).await.unwrap().unwrap();
+    Wager::load(&account.data).unwrap().phase
+}
+
+pub fn print_wager(wager: &Wager) {
+    println!("📋 WAGER INFO:");
+    println!("  Creator:     {}", wager.creator);
+    println!("  Contract:    {}", wager.contract);
+    println!("  Mint:        {}", wager.mint);
+    println!("  Vault:       {}", wager.vault);
+    println!("  Vault Bump:  {}", wager.vault_bump);
+    println!("  Seat Count:  {}", wager.seat_count);
+    println!("  Capacity:    {}", wager.capacity);
+    println!("  Stake:       {} lamports", wager.stake);
+    println!("  Join By:     {}", wager.join_deadline);
+    println!("  Lock By:     {}", wager.lock_deadline);
+    println!("  Resolve By:  {}", wager.resolve_deadline);
+    println!("  Outcome:     {:?}", wager.outcome);
+    println!("  Phase:       {:?}", wager.phase);
+    println!("\n");
+}
+
+pub fn print_seat(seat: &Seat) {
+    println!("👤 SEAT INFO:");
+    println!("  Wager:       {}", seat.wager);
+    println!("  Authority:   {}", seat.authority);
+    println!("  Belief:      {}", seat.belief);
+    println!("  Status:      {:?}", seat.status);
+    println!("  Judgment:    {:?}", seat.judgment);
+    println!("  Last Change: {}", seat.last_change_at);
+    println!("\n");
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{Setup, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{arbiter_judgment_ix, escalate_dispute_ix, render_payouts_ix};
+use solana_god::state::{Fallback, Judgment};
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::BanksClientError;
+
+use solana_sdk::{
+    signature::Signer,
+    signer::keypair::Keypair
+};
+
+async fn create(setup: &mut Setup, arbiter: Option<Pubkey>, fallback: Fallback) -> Result<Pubkey, BanksClientError> {
+    let mut terms = setup.terms();
+    terms.arbiter = arbiter;
+    terms.join_deadline = setup.now + 100;
+    terms.lock_deadline = setup.now + 200;
+    terms.resolve_deadline = setup.now + 300;
+    terms.fallback = fallback;
+    terms.dispute_window = 50;
+
+    let authorities = setup.authorities();
+    setup.create(&terms, &authorities).await
+}
+
+async fn rule(setup: &mut Setup, signer: &Keypair, wager: &Pubkey, judgment: Judgment) -> Result<(), BanksClientError> {
+    let instruction = arbiter_judgment_ix(&setup.program_id, &signer.pubkey(), wager, judgment);
+    setup.send(&[instruction], &[signer]).await
+}
+
+#[tokio::test]
+async fn arbiter_breaks_disagreeing_judgments() {
+    let mut setup = Setup::new(2).await;
+    let arbiter = Keypair::new();
+    let wager = create(&mut setup, Some(arbiter.pubkey()), Fallback::Arbiter).await.unwrap();
+    setup.lock_all(&wager, &[65, 15]).await;
+
+    // Seats get the first say
+    setup.judge(&wager, 0, Judgment::Landed).await.unwrap();
+    let result = rule(&mut setup, &arbiter, &wager, Judgment::Push).await;
+    assert_eq!(wager_error(result), Some(WagerError::ArbitrationNotAllowed));
+
+    // Disagreeing opens a dispute the seats can still amend
+    setup.judge(&wager, 1, Judgment::Missed).await.unwrap();
+    let result = rule(&mut setup, &arbiter, &wager, Judgment::Push).await;
+    assert_eq!(wager_error(result), Some(WagerError::ArbitrationNotAllowed));
+
+    setup.warp_to(setup.now + 51).await;
+
+    let stranger = Keypair::new();
+    let result = rule(&mut setup, &stranger, &wager, Judgment::Landed).await;
+    assert_eq!(wager_error(result), Some(WagerError::NotWagerArbiter));
+
+    rule(&mut setup, &arbiter, &wager, Judgment::Push).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.outcome, Judgment::Push);
+
+    let result = rule(&mut setup, &arbiter, &wager, Judgment::Landed).await;
+    assert_eq!(wager_error(result), Some(WagerError::AlreadyResolved));
+
+    // A push refunds both stakes
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+
+    let instruction = render_payouts_ix(&setup.program_id, &setup.payer(), &wager, &authorities);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    for (authority, balance) in authorities.iter().zip(balances) {
+        assert_eq!(setup.balance(authority).await, balance + STAKE);
+    }
+}
+
+#[tokio::test]
+async fn arbiter_settles_overdue_judgments() {
+    let mut setup = Setup::new(2).await;
+    let arbiter = Keypair::new();
+    let wager = create(&mut setup, Some(arbiter.pubkey()), Fallback::Push).await.unwrap();
+    setup.lock_all(&wager, &[65, 15]).await;
+
+    setup.judge(&wager, 0, Judgment::Landed).await.unwrap();
+
+    // The second seat never judges; once the window closes the arbiter decides
+    setup.warp_to(setup.now + 301).await;
+
+    rule(&mut setup, &arbiter, &wager, Judgment::Landed).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.outcome, Judgment::Landed);
+}
+
+#[tokio::test]
+async fn absent_arbiter_pushes_after_settle_deadline() {
+    let mut setup = Setup::new(2).await;
+    let arbiter = Keypair::new();
+    let wager = create(&mut setup, Some(arbiter.pubkey()), Fallback::Arbiter).await.unwrap();
+    setup.lock_all(&wager, &[65, 15]).await;
+
+    setup.judge(&wager, 0, Judgment::Landed).await.unwrap();
+    setup.judge(&wager, 1, Judgment::Missed).await.unwrap();
+
+    // The dispute is the arbiter's to rule on while it can
+    setup.warp_to(setup.now + 301).await;
+
+    let instruction = escalate_dispute_ix(&setup.program_id, &setup.payer(), &wager, None);
+    let result = setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidFallback));
+
+    // It never does, so past the resolve deadline and dispute window anyone can push
+    setup.warp_to(setup.now + 351).await;
+
+    let instruction = escalate_dispute_ix(&setup.program_id, &setup.payer(), &wager, None);
+    setup.send(&[instruction], &[]).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.outcome, Judgment::Push);
+
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+
+    let instruction = render_payouts_ix(&setup.program_id, &setup.payer(), &wager, &authorities);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    for (authority, balance) in authorities.iter().zip(balances) {
+        assert_eq!(setup.balance(authority).await, balance + STAKE);
+    }
+}
+
+#[tokio::test]
+async fn arbiter_must_be_impartial_and_configured() {
+    let mut setup = Setup::new(2).await;
+
+    // Seat holders cannot arbitrate their own wager
+    let seat_holder = setup.wallets[0].pubkey();
+    let result = create(&mut setup, Some(seat_holder), Fallback::Push).await;
+    assert_eq!(wager_error(result.map(|_| ())), Some(WagerError::ArbiterCannotHoldSeat));
+
+    let wager = create(&mut setup, None, Fallback::Push).await.unwrap();
+    setup.lock_all(&wager, &[65, 15]).await;
+    setup.judge(&wager, 0, Judgment::Landed).await.unwrap();
+    setup.judge(&wager, 1, Judgment::Missed).await.unwrap();
+
+    let arbiter = Keypair::new();
+    let result = rule(&mut setup, &arbiter, &wager, Judgment::Push).await;
+    assert_eq!(wager_error(result), Some(WagerError::ArbiterNotConfigured));
+}
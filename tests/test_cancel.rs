@@ -0,0 +1,101 @@
+mod common;
+
+use common::{Setup, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{find_vault_address, deposit_ix, cancel_wager_ix};
+use solana_god::state::Phase;
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::BanksClientError;
+
+use solana_sdk::{
+    signature::Signer,
+    signer::keypair::Keypair
+};
+
+// A wager with one reserved seat per wallet
+async fn setup(seat_count: usize) -> (Setup, Pubkey) {
+    let mut setup = Setup::new(seat_count).await;
+    let terms = setup.terms();
+    let wager = setup.create_for_all(&terms).await;
+    (setup, wager)
+}
+
+async fn cancel(setup: &mut Setup, wager: &Pubkey, signer: &Keypair) -> Result<(), BanksClientError> {
+    let instruction = cancel_wager_ix(&setup.program_id, &signer.pubkey(), wager, &setup.authorities());
+    setup.send(&[instruction], &[signer]).await
+}
+
+#[tokio::test]
+async fn creator_cancels_and_refunds_staked_seats() {
+    let (mut setup, wager) = setup(3).await;
+
+    setup.stake(&wager, 0, 50).await;
+    setup.stake(&wager, 1, 50).await;
+    setup.lock(&wager, 0).await.unwrap();
+
+    // With a seat locked in, only the creator may back out
+    let open_holder = setup.wallets[2].insecure_clone();
+    let result = cancel(&mut setup, &wager, &open_holder).await;
+    assert_eq!(wager_error(result), Some(WagerError::CancelNotAllowed));
+
+    let stranger = Keypair::new();
+    let result = cancel(&mut setup, &wager, &stranger).await;
+    assert_eq!(wager_error(result), Some(WagerError::CancelNotAllowed));
+
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+
+    let creator = setup.context.payer.insecure_clone();
+    cancel(&mut setup, &wager, &creator).await.unwrap();
+
+    assert_eq!(setup.balance(&authorities[0]).await, balances[0] + STAKE);
+    assert_eq!(setup.balance(&authorities[1]).await, balances[1] + STAKE);
+    assert_eq!(setup.balance(&authorities[2]).await, balances[2]);
+
+    let (vault_pda, _) = find_vault_address(&setup.program_id, &wager);
+    let rent = setup.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(setup.balance(&vault_pda).await, rent.minimum_balance(0));
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Cancelled);
+
+    // A cancelled wager takes no more stakes and cannot be cancelled again
+    let instruction = deposit_ix(&setup.program_id, &open_holder.pubkey(), &wager, 2, STAKE);
+    let result = setup.send(&[instruction], &[&open_holder]).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidPhase));
+
+    let result = cancel(&mut setup, &wager, &creator).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidPhase));
+}
+
+#[tokio::test]
+async fn seat_holder_cancels_before_any_lock() {
+    let (mut setup, wager) = setup(2).await;
+
+    setup.stake(&wager, 0, 50).await;
+
+    let staked = setup.authorities()[0];
+    let balance = setup.balance(&staked).await;
+
+    let holder = setup.wallets[1].insecure_clone();
+    cancel(&mut setup, &wager, &holder).await.unwrap();
+
+    assert_eq!(setup.balance(&staked).await, balance + STAKE);
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Cancelled);
+}
+
+#[tokio::test]
+async fn locked_wager_cannot_be_cancelled() {
+    let (mut setup, wager) = setup(2).await;
+
+    setup.stake(&wager, 0, 50).await;
+    setup.stake(&wager, 1, 50).await;
+    setup.lock(&wager, 0).await.unwrap();
+    setup.lock(&wager, 1).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Locked);
+
+    let creator = setup.context.payer.insecure_clone();
+    let result = cancel(&mut setup, &wager, &creator).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidPhase));
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{Setup, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{
+    find_wager_address,
+    find_vault_address,
+    find_seat_address,
+    create_wager_ix,
+    deposit_ix,
+    render_payouts_ix,
+    cancel_wager_ix,
+    close_wager_ix,
+};
+use solana_god::state::Judgment;
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::BanksClientError;
+
+use solana_sdk::{
+    signature::Signer,
+    signer::keypair::Keypair
+};
+
+// Creates a two-seat wager whose rent is paid by a dedicated creator, so the
+// transaction fee payer never muddies the creator's balance
+async fn setup() -> (Setup, Keypair, Pubkey) {
+    let mut setup = Setup::new(2).await;
+    let creator = Keypair::new();
+    setup.fund(&[creator.pubkey()]).await;
+
+    let terms = setup.terms();
+    let (wager, _) = find_wager_address(&setup.program_id, &creator.pubkey(), 0);
+    let instruction = create_wager_ix(&setup.program_id, &creator.pubkey(), &wager, &terms, &setup.authorities());
+    setup.send(&[instruction], &[&creator]).await.unwrap();
+
+    (setup, creator, wager)
+}
+
+// Wager, vault and seat addresses whose rent a close reclaims
+fn rent_accounts(setup: &Setup, wager: &Pubkey) -> Vec<Pubkey> {
+    let mut accounts = vec![
+        *wager,
+        find_vault_address(&setup.program_id, wager).0,
+    ];
+    accounts.extend((0..2).map(|index| find_seat_address(&setup.program_id, wager, index).0));
+    accounts
+}
+
+async fn close(setup: &mut Setup, wager: &Pubkey, creator: &Pubkey) -> Result<(), BanksClientError> {
+    let instruction = close_wager_ix(&setup.program_id, &setup.payer(), wager, creator, 2, &[]);
+    setup.send(&[instruction], &[]).await
+}
+
+// Closes the wager and checks every lamport of rent reached the creator
+async fn close_and_reclaim(setup: &mut Setup, wager: &Pubkey, creator: &Pubkey) {
+    let accounts = rent_accounts(setup, wager);
+    let reclaimable: u64 = setup.balances(&accounts).await.iter().sum();
+    let balance = setup.balance(creator).await;
+
+    close(setup, wager, creator).await.unwrap();
+
+    assert_eq!(setup.balance(creator).await, balance + reclaimable);
+
+    for account in accounts {
+        assert!(!setup.exists(&account).await);
+    }
+}
+
+#[tokio::test]
+async fn closes_cancelled_wager() {
+    let (mut setup, creator, wager) = setup().await;
+    let wallet = setup.authorities()[0];
+
+    let instruction = deposit_ix(&setup.program_id, &wallet, &wager, 0, STAKE);
+    setup.play(0, &[instruction]).await.unwrap();
+
+    // Live wagers keep their accounts
+    let result = close(&mut setup, &wager, &creator.pubkey()).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidPhase));
+
+    let instruction = cancel_wager_ix(&setup.program_id, &creator.pubkey(), &wager, &setup.authorities());
+    setup.send(&[instruction], &[&creator]).await.unwrap();
+
+    // Rent only goes back to the recorded creator
+    let result = close(&mut setup, &wager, &wallet).await;
+    assert_eq!(wager_error(result), Some(WagerError::NotWagerCreator));
+
+    close_and_reclaim(&mut setup, &wager, &creator.pubkey()).await;
+}
+
+#[tokio::test]
+async fn closes_settled_wager() {
+    let (mut setup, creator, wager) = setup().await;
+
+    setup.lock_all(&wager, &[50, 50]).await;
+    for index in 0..2 {
+        setup.judge(&wager, index, Judgment::Push).await.unwrap();
+    }
+
+    let instruction = render_payouts_ix(&setup.program_id, &setup.payer(), &wager, &setup.authorities());
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    close_and_reclaim(&mut setup, &wager, &creator.pubkey()).await;
+}
@@ -0,0 +1,178 @@
+// Compute benchmarks only mean something against the SBF build, where PDA
+// derivation is metered:
+//
+//     cargo test-sbf --test test_compute
+//
+// The program and creator are pinned so the creator's first wager has every
+// seat at bump 255 and its second has seats well below it. Searching for a
+// seat's bump pays one derivation per bump skipped, so with stored bumps both
+// wagers have to lock for the same cost. The bump layout itself is checked by
+// the regular test run.
+
+mod common;
+
+use solana_god::sdk::{find_wager_address, find_seat_address};
+
+use solana_program::pubkey::Pubkey;
+
+use solana_sdk::{
+    signature::Signer,
+    signer::keypair::{keypair_from_seed, Keypair},
+};
+
+const SEATS: u8 = 10;
+
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+const CREATOR_SEED: u32 = 13_161;
+
+// Cost of one create_program_address; find_program_address pays it for
+// every bump it tries, starting from 255
+const PDA_DERIVATION_UNITS: u64 = 1_500;
+
+// Room for the two wagers differing in more than their seat bumps
+const TOLERANCE_UNITS: u64 = 50 * SEATS as u64;
+
+fn pinned_creator() -> Keypair {
+    let mut seed = [0; 32];
+    seed[..4].copy_from_slice(&CREATOR_SEED.to_le_bytes());
+    keypair_from_seed(&seed).unwrap()
+}
+
+// Bumps find_program_address tries and rejects across the wager's seats
+fn skipped_bumps(wager: &Pubkey) -> u64 {
+    (0..SEATS)
+        .map(|index| {
+            let (_, bump) = find_seat_address(&PROGRAM_ID, wager, index);
+            (u8::MAX - bump) as u64
+        })
+        .sum()
+}
+
+#[test]
+fn pinned_wagers_differ_only_in_seat_bumps() {
+    let creator = pinned_creator().pubkey();
+    let (level, _) = find_wager_address(&PROGRAM_ID, &creator, 0);
+    let (spread, _) = find_wager_address(&PROGRAM_ID, &creator, 1);
+
+    assert_eq!(skipped_bumps(&level), 0);
+
+    // Enough skipped bumps that a search could not hide in the tolerance
+    let skipped = skipped_bumps(&spread);
+    assert!(skipped > 0);
+    assert!(skipped * PDA_DERIVATION_UNITS > TOLERANCE_UNITS);
+}
+
+#[cfg(feature = "test-sbf")]
+mod sbf {
+    use super::*;
+
+    use common::send;
+
+    use solana_god::sdk::{
+        create_batched_wager_ix,
+        init_seats_ix,
+        deposit_ix,
+        update_belief_ix,
+        lock_ix,
+        WagerTerms,
+    };
+    use solana_god::state::Fallback;
+
+    use solana_program::clock::Clock;
+
+    use solana_system_interface::instruction as system_instruction;
+
+    use solana_program_test::*;
+
+    use solana_sdk::transaction::Transaction;
+
+    const STAKE: u64 = 100_000_000; // 0.1 SOL
+
+    // Ceiling for a LockStatus that reads all ten seats
+    const LOCK_BUDGET: u64 = 40_000;
+
+    // Sets up the creator's ten-seat wager with `nonce` and simulates the
+    // first seat's LockStatus, returning the units it consumed
+    async fn lock_units(context: &mut ProgramTestContext, creator: &Keypair, nonce: u64) -> u64 {
+        let payer = context.payer.insecure_clone();
+        let player = Keypair::new();
+        let transfers = [
+            system_instruction::transfer(&payer.pubkey(), &creator.pubkey(), 1_000_000_000),
+            system_instruction::transfer(&payer.pubkey(), &player.pubkey(), 1_000_000_000),
+        ];
+        send(context, &transfers, &[]).await.unwrap();
+
+        let mut authorities = vec![player.pubkey()];
+        authorities.extend((1..SEATS).map(|_| Pubkey::new_unique()));
+
+        let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+        let terms = WagerTerms {
+            contract: Pubkey::new_unique(),
+            mint: None,
+            oracle: None,
+            arbiter: None,
+            capacity: SEATS,
+            stake: STAKE,
+            join_deadline: now + 3_600,
+            lock_deadline: now + 7_200,
+            resolve_deadline: now + 86_400,
+            fallback: Fallback::Push,
+            dispute_window: 3_600,
+        };
+
+        let (wager, _) = find_wager_address(&PROGRAM_ID, &creator.pubkey(), nonce);
+        let instructions = [
+            create_batched_wager_ix(&PROGRAM_ID, &creator.pubkey(), &wager, &terms, SEATS, &authorities[..5]),
+            init_seats_ix(&PROGRAM_ID, &creator.pubkey(), &wager, 5, &authorities[5..]),
+        ];
+        for instruction in instructions {
+            send(context, &[instruction], &[creator]).await.unwrap();
+        }
+
+        let instructions = [
+            deposit_ix(&PROGRAM_ID, &player.pubkey(), &wager, 0, STAKE),
+            update_belief_ix(&PROGRAM_ID, &player.pubkey(), &wager, SEATS, 0, 65),
+        ];
+        send(context, &instructions, &[&player]).await.unwrap();
+
+        // LockStatus verifies every seat of the wager
+        let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[lock_ix(&PROGRAM_ID, &player.pubkey(), &wager, SEATS, 0)],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer, &player], recent_blockhash);
+
+        let simulation = context.banks_client.simulate_transaction(transaction).await.unwrap();
+        assert!(simulation.result.unwrap().is_ok());
+        simulation.simulation_details.unwrap().units_consumed
+    }
+
+    #[tokio::test]
+    async fn stored_bumps_keep_seat_checks_cheap() {
+        let mut program_test = ProgramTest::new("solana_god", PROGRAM_ID, None);
+        program_test.prefer_bpf(true);
+        let mut context = program_test.start_with_context().await;
+
+        let creator = pinned_creator();
+        let level = lock_units(&mut context, &creator, 0).await;
+        let spread = lock_units(&mut context, &creator, 1).await;
+
+        let (spread_wager, _) = find_wager_address(&PROGRAM_ID, &creator.pubkey(), 1);
+        let skipped = skipped_bumps(&spread_wager);
+        assert!(skipped > 0);
+
+        println!(
+            "LockStatus over {SEATS} seats: {level} units at bump 255, {spread} units with {skipped} bumps skipped \
+             (a search would add {} units)",
+            skipped * PDA_DERIVATION_UNITS,
+        );
+
+        assert!(
+            level.abs_diff(spread) <= TOLERANCE_UNITS,
+            "seats below bump 255 cost {} units more, so their bumps are being searched for",
+            spread as i64 - level as i64,
+        );
+        assert!(level <= LOCK_BUDGET, "LockStatus used {level} units, over the {LOCK_BUDGET} budget");
+    }
+}
@@ -0,0 +1,153 @@
+use borsh::BorshDeserialize;
+
+use solana_god::cpi;
+use solana_god::instruction::WagerInstruction;
+use solana_god::sdk::{
+    find_wager_address,
+    find_vault_address,
+    find_seat_address,
+    create_wager_ix,
+    deposit_ix,
+    update_belief_ix,
+    lock_ix,
+    WagerTerms,
+};
+use solana_god::state::{Seat, Status, Fallback};
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use solana_system_interface::instruction as system_instruction;
+
+use solana_program_test::*;
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    signature::Signer,
+    transaction::Transaction,
+    signer::keypair::Keypair
+};
+
+// A program that holds a seat through its own PDA and plays it via CPI
+fn player_program(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (wager_program, accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let (_, bump) = Pubkey::find_program_address(&[b"player"], program_id);
+    let seeds: &[&[u8]] = &[b"player", &[bump]];
+
+    match WagerInstruction::unpack(instruction_data)? {
+        WagerInstruction::ProcessDeposit { index, amount } => {
+            cpi::deposit(wager_program, accounts, index, amount, &[seeds])
+        }
+        WagerInstruction::UpdateBelief { index, belief } => {
+            cpi::update_belief(wager_program, accounts, index, belief, &[seeds])
+        }
+        WagerInstruction::LockStatus { index } => {
+            cpi::lock(wager_program, accounts, index, &[seeds])
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+// Routes a wager instruction through the player program, which signs for its PDA
+fn through_player(player_id: &Pubkey, instruction: Instruction) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(instruction.program_id, false)];
+    accounts.extend(instruction.accounts.into_iter().map(|mut meta| {
+        meta.is_signer = false;
+        meta
+    }));
+
+    Instruction {
+        program_id: *player_id,
+        accounts,
+        data: instruction.data,
+    }
+}
+
+#[tokio::test]
+async fn program_plays_seat_via_cpi() {
+    let program_id = Pubkey::new_unique();
+    let player_id = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::new(
+        "solana_god",
+        program_id,
+        processor!(solana_god::process_instruction),
+    );
+    program_test.add_program("player", player_id, processor!(player_program));
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let (player_pda, _) = Pubkey::find_program_address(&[b"player"], &player_id);
+    let wallet_b = Keypair::new();
+    let (wager_key, _) = find_wager_address(&program_id, &payer.pubkey(), 0);
+    let stake_amount: u64 = 100_000_000; // 0.1 SOL
+
+    let reserved_seats = vec![player_pda, wallet_b.pubkey()];
+
+    let now = banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let terms = WagerTerms {
+        contract: Pubkey::new_unique(),
+        mint: None,
+        oracle: None,
+        arbiter: None,
+        capacity: 2,
+        stake: stake_amount,
+        join_deadline: now + 3_600,
+        lock_deadline: now + 7_200,
+        resolve_deadline: now + 86_400,
+        fallback: Fallback::Push,
+        dispute_window: 3_600,
+    };
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &player_pda, 1_000_000_000),
+            create_wager_ix(
+                &program_id,
+                &payer.pubkey(),
+                &wager_key,
+                &terms,
+                &reserved_seats,
+            ),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // The player program funds, forecasts and locks its seat
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            through_player(&player_id, deposit_ix(&program_id, &player_pda, &wager_key, 0, stake_amount)),
+            through_player(&player_id, update_belief_ix(&program_id, &player_pda, &wager_key, 2, 0, 70)),
+            through_player(&player_id, lock_ix(&program_id, &player_pda, &wager_key, 2, 0)),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let (seat_pda, _) = find_seat_address(&program_id, &wager_key, 0);
+    let seat = banks_client.get_account(seat_pda).await.unwrap().unwrap();
+    let seat_data = Seat::try_from_slice(&seat.data).unwrap();
+    assert_eq!(seat_data.authority, player_pda);
+    assert_eq!(seat_data.belief, 70);
+    assert_eq!(seat_data.status, Status::Locked);
+
+    let (vault_pda, _) = find_vault_address(&program_id, &wager_key);
+    let vault = banks_client.get_account(vault_pda).await.unwrap().unwrap();
+    let rent = banks_client.get_rent().await.unwrap();
+    assert_eq!(vault.lamports, rent.minimum_balance(0) + stake_amount);
+}
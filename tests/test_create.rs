@@ -0,0 +1,133 @@
+mod common;
+
+use borsh::to_vec;
+
+use common::{Setup, instruction_error, wager_error};
+
+use solana_god::error::WagerError;
+use solana_god::instruction::WagerInstruction;
+use solana_god::sdk::{
+    find_counter_address,
+    find_wager_address,
+    find_vault_address,
+    find_seat_address,
+    creator_wager_addresses,
+    create_wager_ix,
+    WagerTerms,
+};
+use solana_god::state::WagerCounter;
+
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+
+fn wager_address(setup: &Setup, nonce: u64) -> Pubkey {
+    find_wager_address(&setup.program_id, &setup.payer(), nonce).0
+}
+
+fn create_ix(setup: &Setup, nonce: u64, terms: &WagerTerms, reserved_seats: &[Pubkey]) -> Instruction {
+    create_wager_ix(&setup.program_id, &setup.payer(), &wager_address(setup, nonce), terms, reserved_seats)
+}
+
+async fn wager_count(setup: &mut Setup) -> u64 {
+    let (counter, _) = find_counter_address(&setup.program_id, &setup.payer());
+    let account = setup.context.banks_client.get_account(counter).await.unwrap().unwrap();
+    WagerCounter::load(&account.data).unwrap().wager_count
+}
+
+#[tokio::test]
+async fn wagers_are_found_from_the_creator_counter() {
+    let mut setup = Setup::new(0).await;
+    let terms = WagerTerms { capacity: 2, ..setup.terms() };
+
+    for nonce in 0..2 {
+        let instruction = create_ix(&setup, nonce, &terms, &[Pubkey::new_unique()]);
+        setup.send(&[instruction], &[]).await.unwrap();
+    }
+
+    let creator = setup.payer();
+    let count = wager_count(&mut setup).await;
+    assert_eq!(count, 2);
+
+    let wagers = creator_wager_addresses(&setup.program_id, &creator, count);
+    assert_eq!(wagers, vec![wager_address(&setup, 0), wager_address(&setup, 1)]);
+
+    for wager in &wagers {
+        assert_eq!(setup.wager_state(wager).await.creator, creator);
+    }
+
+    // Only the next nonce can be created
+    let instruction = create_ix(&setup, 3, &terms, &[Pubkey::new_unique()]);
+    let result = setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidWagerAddress));
+
+    // Existing wagers are never overwritten
+    let instruction = create_ix(&setup, 1, &terms, &[Pubkey::new_unique()]);
+    let result = setup.send(&[instruction], &[]).await;
+    assert_eq!(instruction_error(result), Some(InstructionError::AccountAlreadyInitialized));
+    assert_eq!(wager_count(&mut setup).await, 2);
+}
+
+#[tokio::test]
+async fn program_records_vault_and_seat_count() {
+    let mut setup = Setup::new(0).await;
+    let reserved_seats = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let terms = WagerTerms { capacity: 2, ..setup.terms() };
+
+    // A client lying about the vault and seat count is overruled
+    let mut instruction = create_ix(&setup, 0, &terms, &reserved_seats);
+    let Ok(WagerInstruction::CreateWager { wager: mut fields, reserved_seats }) = WagerInstruction::unpack(&instruction.data) else {
+        panic!("create_wager_ix encodes CreateWager");
+    };
+    fields.vault = Pubkey::new_unique();
+    fields.vault_bump = 0;
+    fields.seat_count = 7;
+    instruction.data = to_vec(&WagerInstruction::CreateWager { wager: fields, reserved_seats }).unwrap();
+
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    let wager = wager_address(&setup, 0);
+    let state = setup.wager_state(&wager).await;
+    assert_eq!((state.vault, state.vault_bump), find_vault_address(&setup.program_id, &wager));
+    assert_eq!(state.seat_count, 2);
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_terms() {
+    let mut setup = Setup::new(0).await;
+    let terms = WagerTerms { capacity: 2, ..setup.terms() };
+
+    let instruction = create_ix(&setup, 0, &WagerTerms { stake: 0, ..terms }, &[Pubkey::new_unique()]);
+    let result = setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::ZeroStake));
+
+    let authority = Pubkey::new_unique();
+    let instruction = create_ix(&setup, 0, &terms, &[authority, authority]);
+    let result = setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::DuplicateSeatAuthority));
+
+    let wager = wager_address(&setup, 0);
+    assert!(!setup.exists(&wager).await);
+}
+
+#[tokio::test]
+async fn prefunded_addresses_do_not_block_creation() {
+    let mut setup = Setup::new(0).await;
+    let terms = WagerTerms { capacity: 2, ..setup.terms() };
+
+    // Every address of the next wager is known ahead of time and anyone can
+    // send lamports to it
+    let wager = wager_address(&setup, 0);
+    let (counter, _) = find_counter_address(&setup.program_id, &setup.payer());
+    let (vault, _) = find_vault_address(&setup.program_id, &wager);
+    let seat = find_seat_address(&setup.program_id, &wager, 0).0;
+    setup.fund(&[counter, wager, vault, seat]).await;
+
+    let instruction = create_ix(&setup, 0, &terms, &[Pubkey::new_unique()]);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    assert_eq!(wager_count(&mut setup).await, 1);
+    assert_eq!(setup.wager_state(&wager).await.seat_count, 1);
+    assert!(setup.seat(&wager, 0).await.is_some());
+}
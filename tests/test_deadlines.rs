@@ -0,0 +1,91 @@
+mod common;
+
+use common::{Setup, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{deposit_ix, update_belief_ix, lock_ix, WagerTerms};
+use solana_god::state::Judgment;
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::BanksClientError;
+
+use solana_sdk::signature::Signer;
+
+fn deadlines(setup: &Setup, join_deadline: i64, lock_deadline: i64, resolve_deadline: i64) -> WagerTerms {
+    WagerTerms { join_deadline, lock_deadline, resolve_deadline, ..setup.terms() }
+}
+
+async fn create(setup: &mut Setup, terms: &WagerTerms) -> Result<Pubkey, BanksClientError> {
+    let authorities = setup.authorities();
+    setup.create(terms, &authorities).await
+}
+
+#[tokio::test]
+async fn create_rejects_invalid_deadlines() {
+    let mut setup = Setup::new(2).await;
+    let now = setup.now;
+
+    // Join deadline already passed
+    let terms = deadlines(&setup, now, now + 200, now + 300);
+    let result = create(&mut setup, &terms).await;
+    assert_eq!(wager_error(result.map(|_| ())), Some(WagerError::InvalidDeadlines));
+
+    // Lock deadline before join deadline
+    let terms = deadlines(&setup, now + 200, now + 100, now + 300);
+    let result = create(&mut setup, &terms).await;
+    assert_eq!(wager_error(result.map(|_| ())), Some(WagerError::InvalidDeadlines));
+
+    // Resolve deadline before lock deadline
+    let terms = deadlines(&setup, now + 100, now + 300, now + 200);
+    let result = create(&mut setup, &terms).await;
+    assert_eq!(wager_error(result.map(|_| ())), Some(WagerError::InvalidDeadlines));
+
+    let terms = deadlines(&setup, now + 100, now + 200, now + 300);
+    create(&mut setup, &terms).await.unwrap();
+}
+
+#[tokio::test]
+async fn actions_close_with_their_window() {
+    let mut setup = Setup::new(2).await;
+    let now = setup.now;
+    let program_id = setup.program_id;
+    let [wallet_a, wallet_b] = [setup.wallets[0].pubkey(), setup.wallets[1].pubkey()];
+
+    let terms = deadlines(&setup, now + 100, now + 200, now + 300);
+    let wager = create(&mut setup, &terms).await.unwrap();
+
+    setup.play(0, &[deposit_ix(&program_id, &wallet_a, &wager, 0, STAKE)]).await.unwrap();
+
+    // Past the join deadline nobody can fund a seat
+    setup.warp_to(now + 101).await;
+    let result = setup.play(1, &[deposit_ix(&program_id, &wallet_b, &wager, 1, STAKE)]).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+
+    // Beliefs and locks stay open until the lock deadline
+    setup.play(0, &[update_belief_ix(&program_id, &wallet_a, &wager, 2, 0, 60)]).await.unwrap();
+
+    setup.warp_to(now + 201).await;
+    let result = setup.play(0, &[update_belief_ix(&program_id, &wallet_a, &wager, 2, 0, 70)]).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+
+    let result = setup.play(0, &[lock_ix(&program_id, &wallet_a, &wager, 2, 0)]).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+}
+
+#[tokio::test]
+async fn judgments_close_at_resolve_deadline() {
+    let mut setup = Setup::new(2).await;
+    let now = setup.now;
+
+    let terms = deadlines(&setup, now + 100, now + 200, now + 300);
+    let wager = create(&mut setup, &terms).await.unwrap();
+
+    setup.lock_all(&wager, &[60, 30]).await;
+    setup.judge(&wager, 0, Judgment::Landed).await.unwrap();
+
+    // Past the resolve deadline the remaining seat can no longer judge
+    setup.warp_to(now + 301).await;
+    let result = setup.judge(&wager, 1, Judgment::Landed).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+}
@@ -0,0 +1,230 @@
+mod common;
+
+use common::{Setup, program_test, preload_attestation, instruction_error, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{escalate_dispute_ix, render_payouts_ix, WagerTerms};
+use solana_god::state::{Phase, Fallback, Judgment};
+
+use solana_program::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+};
+
+use solana_program_test::BanksClientError;
+
+const DISPUTE_WINDOW: i64 = 600;
+
+fn dispute_terms(setup: &Setup, oracle: Option<Pubkey>, fallback: Fallback) -> WagerTerms {
+    WagerTerms { oracle, fallback, dispute_window: DISPUTE_WINDOW, ..setup.terms() }
+}
+
+// Locks the wager and leaves both seats disagreeing
+async fn dispute(setup: &mut Setup, wager: &Pubkey) {
+    setup.lock_all(wager, &[65, 15]).await;
+    setup.judge(wager, 0, Judgment::Landed).await.unwrap();
+    setup.judge(wager, 1, Judgment::Missed).await.unwrap();
+}
+
+async fn escalate(setup: &mut Setup, wager: &Pubkey, attestation: Option<&Pubkey>) -> Result<(), BanksClientError> {
+    let instruction = escalate_dispute_ix(&setup.program_id, &setup.payer(), wager, attestation);
+    setup.send(&[instruction], &[]).await
+}
+
+async fn render(setup: &mut Setup, wager: &Pubkey) -> Result<(), BanksClientError> {
+    let instruction = render_payouts_ix(&setup.program_id, &setup.payer(), wager, &setup.authorities());
+    setup.send(&[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn amendments_settle_a_dispute() {
+    let mut setup = Setup::new(2).await;
+
+    let terms = dispute_terms(&setup, None, Fallback::Push);
+    let wager = setup.create_for_all(&terms).await;
+    dispute(&mut setup, &wager).await;
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!(state.phase, Phase::Disputed);
+    assert_eq!(state.disputed_until, setup.now + DISPUTE_WINDOW);
+
+    // Nothing pays out while the seats disagree
+    let result = render(&mut setup, &wager).await;
+    assert_eq!(wager_error(result), Some(WagerError::JudgmentsDisagree));
+
+    // The window is still open, so the fallback has to wait
+    let result = escalate(&mut setup, &wager, None).await;
+    assert_eq!(wager_error(result), Some(WagerError::DisputeOpen));
+
+    setup.judge(&wager, 1, Judgment::Landed).await.unwrap();
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!(state.phase, Phase::Judging);
+    assert_eq!(state.outcome, Judgment::Landed);
+
+    render(&mut setup, &wager).await.unwrap();
+}
+
+#[tokio::test]
+async fn judgments_cannot_be_retracted() {
+    let mut setup = Setup::new(2).await;
+
+    let terms = dispute_terms(&setup, None, Fallback::Push);
+    let wager = setup.create_for_all(&terms).await;
+    dispute(&mut setup, &wager).await;
+
+    // A losing seat cannot go back to Pending to keep the dispute from resolving
+    let result = setup.judge(&wager, 1, Judgment::Pending).await;
+    assert_eq!(instruction_error(result), Some(InstructionError::InvalidArgument));
+    assert_eq!(setup.seat(&wager, 1).await.unwrap().judgment, Judgment::Missed);
+}
+
+#[tokio::test]
+async fn push_fallback_refunds_an_expired_dispute() {
+    let mut setup = Setup::new(2).await;
+
+    let terms = dispute_terms(&setup, None, Fallback::Push);
+    let wager = setup.create_for_all(&terms).await;
+    dispute(&mut setup, &wager).await;
+
+    setup.warp_to(setup.now + DISPUTE_WINDOW + 1).await;
+
+    // Amendments close with the window
+    let result = setup.judge(&wager, 1, Judgment::Landed).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+
+    escalate(&mut setup, &wager, None).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.outcome, Judgment::Push);
+
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+    render(&mut setup, &wager).await.unwrap();
+
+    for (balance, refunded) in balances.iter().zip(setup.balances(&authorities).await) {
+        assert_eq!(refunded, balance + STAKE);
+    }
+}
+
+#[tokio::test]
+async fn oracle_fallback_applies_the_attestation() {
+    let program_id = Pubkey::new_unique();
+    let contract = Pubkey::new_unique();
+    let oracle = Pubkey::new_unique();
+
+    // The oracle published its outcome before the wager was disputed
+    let mut program_test = program_test(program_id);
+    let attestation = preload_attestation(&mut program_test, &program_id, &oracle, &contract, Judgment::Missed, 0);
+
+    let mut setup = Setup::start(program_test, program_id, 2).await;
+
+    let terms = WagerTerms { contract, ..dispute_terms(&setup, Some(oracle), Fallback::Oracle) };
+    let wager = setup.create_for_all(&terms).await;
+    dispute(&mut setup, &wager).await;
+
+    setup.warp_to(setup.now + DISPUTE_WINDOW + 1).await;
+
+    escalate(&mut setup, &wager, Some(&attestation)).await.unwrap();
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!(state.phase, Phase::Judging);
+    assert_eq!(state.outcome, Judgment::Missed);
+}
+
+#[tokio::test]
+async fn fallbacks_must_name_a_resolver() {
+    let mut setup = Setup::new(2).await;
+    let authorities = setup.authorities();
+
+    let terms = dispute_terms(&setup, None, Fallback::Arbiter);
+    let result = setup.create(&terms, &authorities).await;
+    assert_eq!(wager_error(result.map(|_| ())), Some(WagerError::InvalidFallback));
+
+    let terms = dispute_terms(&setup, None, Fallback::Oracle);
+    let result = setup.create(&terms, &authorities).await;
+    assert_eq!(wager_error(result.map(|_| ())), Some(WagerError::InvalidFallback));
+
+    let mut terms = dispute_terms(&setup, None, Fallback::Push);
+    terms.dispute_window = -1;
+    let result = setup.create(&terms, &authorities).await;
+    assert_eq!(wager_error(result.map(|_| ())), Some(WagerError::InvalidDeadlines));
+
+    // Arbiter fallbacks are ruled on by the arbiter, not escalated
+    let mut terms = dispute_terms(&setup, None, Fallback::Arbiter);
+    terms.arbiter = Some(Pubkey::new_unique());
+    let wager = setup.create_for_all(&terms).await;
+    dispute(&mut setup, &wager).await;
+
+    setup.warp_to(setup.now + DISPUTE_WINDOW + 1).await;
+
+    let result = escalate(&mut setup, &wager, None).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidFallback));
+}
+
+#[tokio::test]
+async fn missing_judgments_fall_back_after_resolve_deadline() {
+    let mut setup = Setup::new(2).await;
+
+    let terms = dispute_terms(&setup, None, Fallback::Push);
+    let resolve_deadline = terms.resolve_deadline;
+    let wager = setup.create_for_all(&terms).await;
+
+    // The losing seat never judges
+    setup.lock_all(&wager, &[65, 15]).await;
+    setup.judge(&wager, 0, Judgment::Landed).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Judging);
+
+    // It still has until the resolve deadline
+    let result = escalate(&mut setup, &wager, None).await;
+    assert_eq!(wager_error(result), Some(WagerError::DisputeOpen));
+
+    setup.warp_to(resolve_deadline + 1).await;
+
+    let result = setup.judge(&wager, 1, Judgment::Missed).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+
+    escalate(&mut setup, &wager, None).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.outcome, Judgment::Push);
+
+    let result = escalate(&mut setup, &wager, None).await;
+    assert_eq!(wager_error(result), Some(WagerError::AlreadyResolved));
+
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+    render(&mut setup, &wager).await.unwrap();
+
+    for (balance, refunded) in balances.iter().zip(setup.balances(&authorities).await) {
+        assert_eq!(refunded, balance + STAKE);
+    }
+}
+
+#[tokio::test]
+async fn silent_oracle_pushes_after_settle_deadline() {
+    let mut setup = Setup::new(2).await;
+
+    let terms = dispute_terms(&setup, Some(Pubkey::new_unique()), Fallback::Oracle);
+    let resolve_deadline = terms.resolve_deadline;
+    let wager = setup.create_for_all(&terms).await;
+    dispute(&mut setup, &wager).await;
+
+    // The oracle never publishes, so its fallback has nothing to apply
+    setup.warp_to(setup.now + DISPUTE_WINDOW + 1).await;
+
+    let result = escalate(&mut setup, &wager, None).await;
+    assert_eq!(instruction_error(result), Some(InstructionError::NotEnoughAccountKeys));
+
+    setup.warp_to(resolve_deadline + DISPUTE_WINDOW + 1).await;
+
+    escalate(&mut setup, &wager, None).await.unwrap();
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!(state.phase, Phase::Judging);
+    assert_eq!(state.outcome, Judgment::Push);
+
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+    render(&mut setup, &wager).await.unwrap();
+
+    for (balance, refunded) in balances.iter().zip(setup.balances(&authorities).await) {
+        assert_eq!(refunded, balance + STAKE);
+    }
+}
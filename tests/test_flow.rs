@@ -1,24 +1,36 @@
-use borsh::{BorshDeserialize, to_vec}; // BorshSerialize
+mod common;
+
+use borsh::BorshDeserialize;
+
+use common::{wager_error, wager_phase, print_wager, print_seat};
 
 use solana_god::{process_instruction};
-use solana_god::instruction::{WagerInstruction};
-use solana_god::state::{Wager, Seat, Judgment};
+use solana_god::error::WagerError;
+use solana_god::sdk::{
+    find_wager_address,
+    find_vault_address,
+    find_seat_address,
+    create_wager_ix,
+    deposit_ix,
+    update_belief_ix,
+    lock_ix,
+    set_judgment_ix,
+    render_payouts_ix,
+    WagerTerms,
+};
+use solana_god::state::{Wager, Seat, Phase, Status, Fallback, Judgment};
 
 use solana_program::{
+    clock::Clock,
     pubkey::Pubkey,
-    system_instruction,
-    system_program,
     // hash::hash,
-    // solana_system_interface,
 };
 
-// use solana_system_interface::instruction;
-// use solana_sdk_ids::system_program;
+use solana_system_interface::instruction as system_instruction;
 
 use solana_program_test::*;
 
 use solana_sdk::{
-    instruction::{AccountMeta, Instruction},
     signature::Signer,
     transaction::Transaction,
     signer::keypair::Keypair
@@ -32,7 +44,7 @@ async fn wager_flow() {
     //
 
     let program_id = Pubkey::new_unique();
-    let (banks_client, payer, recent_blockhash) = 
+    let (mut banks_client, payer, recent_blockhash) =
         ProgramTest::new("solana_god", program_id, processor!(process_instruction))
             .start()
             .await;
@@ -44,8 +56,9 @@ async fn wager_flow() {
     let seed_amount: u64 = 1_000_000_000; // 1.0 SOL
     let stake_amount: u64 = 100_000_000; // 0.1 SOL
     let belief_a: u8 = 65;
+    let opening_belief_b: u8 = 20;
     let belief_b: u8 = 15;
-    let wager_account = Keypair::new();
+    let (wager_key, _) = find_wager_address(&program_id, &payer.pubkey(), 0);
 
     let mut transaction = Transaction::new_with_payer(
         &[
@@ -63,86 +76,53 @@ async fn wager_flow() {
     // STEP ONE: Create test wager and vault
     //
 
-    // Derive vault PDA
-    let (vault_pda, vault_bump) = Pubkey::find_program_address(
-        &[b"vault", &wager_account.pubkey().to_bytes()],
-        &program_id,
-    );
+    let (vault_pda, _vault_bump) = find_vault_address(&program_id, &wager_key);
+    let seat_pdas: Vec<Pubkey> = (0..2)
+        .map(|index| find_seat_address(&program_id, &wager_key, index).0)
+        .collect();
+
+    let reserved_seats = vec!(wallet_a.pubkey(), wallet_b.pubkey());
 
-    let wager = Wager {
+    let now = banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let terms = WagerTerms {
         contract: contract_pubkey,
-        vault: vault_pda,
-        vault_bump: vault_bump,
-        seat_count: 0,
+        mint: None,
+        oracle: None,
+        arbiter: None,
         capacity: 2,
         stake: stake_amount,
+        join_deadline: now + 3_600,
+        lock_deadline: now + 7_200,
+        resolve_deadline: now + 86_400,
+        fallback: Fallback::Push,
+        dispute_window: 3_600,
     };
 
-    let reserved_seats = vec!(wallet_a.pubkey(), wallet_b.pubkey());
-
-    // Derive seat PDAs
-    let mut seat_pdas = Vec::new();
-    for i in 0..reserved_seats.len() {
-        let (seat_pda, _seat_bump) = Pubkey::find_program_address(
-            &[
-                b"seat",
-                &wager_account.pubkey().to_bytes(),
-                &(i as u8).to_le_bytes(),
-            ],
-            &program_id,
-        );
-        seat_pdas.push(seat_pda);
-    }
-
-    // Build and encode wager
-    let contract_data = WagerInstruction::CreateWager { wager, reserved_seats };
-    let encoded_data = to_vec(&contract_data).unwrap();
-
-    let mut accounts = vec![
-        AccountMeta::new(payer.pubkey(), true),
-        AccountMeta::new(wager_account.pubkey(), true),
-        AccountMeta::new(vault_pda, false),
-        AccountMeta::new_readonly(system_program::id(), false),
-    ];
-
-    for seat_pda in &seat_pdas {
-        accounts.push(AccountMeta::new(*seat_pda, false));
-    }
-
-    let write_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        accounts,
+    let write_instruction = create_wager_ix(
+        &program_id,
+        &payer.pubkey(),
+        &wager_key,
+        &terms,
+        &reserved_seats,
     );
 
     // Create and send transaction
     let mut write_transaction = Transaction::new_with_payer(
-        &[write_instruction], 
+        &[write_instruction],
         Some(&payer.pubkey())
     );
 
-    write_transaction.sign(&[&payer, &wager_account], recent_blockhash);
+    write_transaction.sign(&[&payer], recent_blockhash);
     banks_client.process_transaction(write_transaction).await.unwrap();
 
-    /*
+    assert_eq!(wager_phase(&mut banks_client, wager_key).await, Phase::Created);
+
     //
     // STEP TWO: Process Deposits
     //
 
     // wallet_a
-    let deposit_data = WagerInstruction::ProcessDeposit { amount: stake_amount };
-    let encoded_data = to_vec(&deposit_data).unwrap();
-
-    let deposit_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new(wallet_a.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false),
-            AccountMeta::new(vault_pda, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ],
-    );
+    let deposit_instruction = deposit_ix(&program_id, &wallet_a.pubkey(), &wager_key, 0, stake_amount);
 
     // Create and send transaction
     let mut deposit_transaction = Transaction::new_with_payer(
@@ -152,20 +132,19 @@ async fn wager_flow() {
     deposit_transaction.sign(&[&payer, &wallet_a], recent_blockhash);
     banks_client.process_transaction(deposit_transaction).await.unwrap();
 
-    // wallet_b
-    let deposit_data = WagerInstruction::ProcessDeposit { amount: stake_amount };
-    let encoded_data = to_vec(&deposit_data).unwrap();
-
-    let deposit_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new(wallet_b.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false),
-            AccountMeta::new(vault_pda, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ],
+    // wallet_b cannot fund wallet_a's seat
+    let deposit_instruction = deposit_ix(&program_id, &wallet_b.pubkey(), &wager_key, 0, stake_amount);
+
+    let mut deposit_transaction = Transaction::new_with_payer(
+        &[deposit_instruction],
+        Some(&payer.pubkey())
     );
+    deposit_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
+    let result = banks_client.process_transaction(deposit_transaction).await;
+    assert_eq!(wager_error(result), Some(WagerError::WrongSeatAuthority));
+
+    // wallet_b
+    let deposit_instruction = deposit_ix(&program_id, &wallet_b.pubkey(), &wager_key, 1, stake_amount);
 
     // Create and send transaction
     let mut deposit_transaction = Transaction::new_with_payer(
@@ -175,23 +154,23 @@ async fn wager_flow() {
     deposit_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
     banks_client.process_transaction(deposit_transaction).await.unwrap();
 
-    
+    for seat_pda in &seat_pdas {
+        let seat = banks_client.get_account(*seat_pda).await.unwrap().unwrap();
+        let seat_data = Seat::try_from_slice(&seat.data).unwrap();
+        assert_eq!(seat_data.status, Status::Staked);
+    }
+
+    let vault = banks_client.get_account(vault_pda).await.unwrap().unwrap();
+    let rent = banks_client.get_rent().await.unwrap();
+    assert_eq!(vault.lamports, rent.minimum_balance(0) + 2 * stake_amount);
+    assert_eq!(wager_phase(&mut banks_client, wager_key).await, Phase::Funding);
+
     //
     // STEP THREE: Update beliefs
     //
 
     // wallet_a
-    let update_data = WagerInstruction::UpdateBelief { belief: belief_a };
-    let encoded_data = to_vec(&update_data).unwrap();
-
-    let update_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new_readonly(wallet_a.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false),
-        ],
-    );
+    let update_instruction = update_belief_ix(&program_id, &wallet_a.pubkey(), &wager_key, 2, 0, belief_a);
 
     // Create and send transaction
     let mut update_transaction = Transaction::new_with_payer(
@@ -201,18 +180,19 @@ async fn wager_flow() {
     update_transaction.sign(&[&payer, &wallet_a], recent_blockhash);
     banks_client.process_transaction(update_transaction).await.unwrap();
 
-    // wallet_b
-    let update_data = WagerInstruction::UpdateBelief { belief: belief_b };
-    let encoded_data = to_vec(&update_data).unwrap();
-
-    let update_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new_readonly(wallet_b.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false),
-        ],
+    // wallet_b cannot record a belief above 100
+    let update_instruction = update_belief_ix(&program_id, &wallet_b.pubkey(), &wager_key, 2, 1, 101);
+
+    let mut update_transaction = Transaction::new_with_payer(
+        &[update_instruction],
+        Some(&payer.pubkey())
     );
+    update_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
+    let result = banks_client.process_transaction(update_transaction).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidBelief));
+
+    // wallet_b
+    let update_instruction = update_belief_ix(&program_id, &wallet_b.pubkey(), &wager_key, 2, 1, opening_belief_b);
 
     // Create and send transaction
     let mut update_transaction = Transaction::new_with_payer(
@@ -222,52 +202,95 @@ async fn wager_flow() {
     update_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
     banks_client.process_transaction(update_transaction).await.unwrap();
 
+    let seat = banks_client.get_account(seat_pdas[0]).await.unwrap().unwrap();
+    assert_eq!(Seat::try_from_slice(&seat.data).unwrap().belief, belief_a);
+
+    let seat = banks_client.get_account(seat_pdas[1]).await.unwrap().unwrap();
+    assert_eq!(Seat::try_from_slice(&seat.data).unwrap().belief, opening_belief_b);
+
     //
     // STEP FOUR: Lock status
     //
 
     // wallet_a
-    let lock_data = WagerInstruction::LockStatus;
-    let encoded_data = to_vec(&lock_data).unwrap();
-
-    let lock_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new_readonly(wallet_a.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false),
-        ],
-    );
+    let lock_instruction_a = lock_ix(&program_id, &wallet_a.pubkey(), &wager_key, 2, 0);
 
     // Create and send transaction
     let mut lock_transaction = Transaction::new_with_payer(
-        &[lock_instruction],
+        std::slice::from_ref(&lock_instruction_a),
+        Some(&payer.pubkey())
+    );
+    lock_transaction.sign(&[&payer, &wallet_a], recent_blockhash);
+    banks_client.process_transaction(lock_transaction).await.unwrap();
+
+    let seat = banks_client.get_account(seat_pdas[0]).await.unwrap().unwrap();
+    assert_eq!(Seat::try_from_slice(&seat.data).unwrap().status, Status::Locked);
+
+    // One lock alone leaves the wager funding
+    assert_eq!(wager_phase(&mut banks_client, wager_key).await, Phase::Funding);
+
+    // wallet_b revises its belief, releasing wallet_a from the old terms
+    let update_instruction = update_belief_ix(&program_id, &wallet_b.pubkey(), &wager_key, 2, 1, belief_b);
+
+    let mut update_transaction = Transaction::new_with_payer(
+        &[update_instruction],
+        Some(&payer.pubkey())
+    );
+    update_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
+    banks_client.process_transaction(update_transaction).await.unwrap();
+
+    let seat = banks_client.get_account(seat_pdas[0]).await.unwrap().unwrap();
+    assert_eq!(Seat::try_from_slice(&seat.data).unwrap().status, Status::Staked);
+
+    // wallet_a agrees to the new terms
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+
+    let mut lock_transaction = Transaction::new_with_payer(
+        &[lock_instruction_a],
         Some(&payer.pubkey())
     );
     lock_transaction.sign(&[&payer, &wallet_a], recent_blockhash);
     banks_client.process_transaction(lock_transaction).await.unwrap();
 
     // wallet_b
-    // TODO: check that payment does not worked until wallet_b is locked
-    // TODO: reset Lock status for both when an account is 
+    let lock_instruction = lock_ix(&program_id, &wallet_b.pubkey(), &wager_key, 2, 1);
+
+    let mut lock_transaction = Transaction::new_with_payer(
+        &[lock_instruction],
+        Some(&payer.pubkey())
+    );
+    lock_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
+    banks_client.process_transaction(lock_transaction).await.unwrap();
+
+    for seat_pda in &seat_pdas {
+        let seat = banks_client.get_account(*seat_pda).await.unwrap().unwrap();
+        let seat_data = Seat::try_from_slice(&seat.data).unwrap();
+        assert_eq!(seat_data.status, Status::Locked);
+        assert_ne!(seat_data.belief, 255);
+    }
+
+    assert_eq!(wager_phase(&mut banks_client, wager_key).await, Phase::Locked);
+
+    // Terms are frozen once the wager is locked
+    let update_instruction = update_belief_ix(&program_id, &wallet_b.pubkey(), &wager_key, 2, 1, opening_belief_b);
+
+    let mut update_transaction = Transaction::new_with_payer(
+        &[update_instruction],
+        Some(&payer.pubkey())
+    );
+    update_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
+    let result = banks_client.process_transaction(update_transaction).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidPhase));
 
-    
     //
     // STEP FIVE: Set approval status
     //
 
     // wallet_a
-    let approval_data = WagerInstruction::SetJudgment { judgment: Judgment::Landed };
-    let encoded_data = to_vec(&approval_data).unwrap();
-
-    let approval_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new_readonly(wallet_a.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false),
-        ],
-    );
+    let approval_instruction = set_judgment_ix(&program_id, &wallet_a.pubkey(), &wager_key, 2, 0, Judgment::Landed);
 
     // Create and send transaction
     let mut approval_transaction = Transaction::new_with_payer(
@@ -277,19 +300,13 @@ async fn wager_flow() {
     approval_transaction.sign(&[&payer, &wallet_a], recent_blockhash);
     banks_client.process_transaction(approval_transaction).await.unwrap();
 
+    // One judgment alone does not resolve the wager
+    let account = banks_client.get_account(wager_key).await.unwrap().unwrap();
+    assert_eq!(Wager::try_from_slice(&account.data).unwrap().outcome, Judgment::Pending);
+    assert_eq!(wager_phase(&mut banks_client, wager_key).await, Phase::Judging);
 
     // wallet_b
-    let approval_data = WagerInstruction::SetJudgment { judgment: Judgment::Landed };
-    let encoded_data = to_vec(&approval_data).unwrap();
-
-    let approval_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new_readonly(wallet_b.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false), 
-        ],
-    );
+    let approval_instruction = set_judgment_ix(&program_id, &wallet_b.pubkey(), &wager_key, 2, 1, Judgment::Landed);
 
     // Create and send transaction
     let mut approval_transaction = Transaction::new_with_payer(
@@ -299,48 +316,73 @@ async fn wager_flow() {
     approval_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
     banks_client.process_transaction(approval_transaction).await.unwrap();
 
+    let account = banks_client.get_account(wager_key).await.unwrap().unwrap();
+    assert_eq!(Wager::try_from_slice(&account.data).unwrap().outcome, Judgment::Landed);
+
+    // wallet_b cannot re-judge a resolved wager
+    let approval_instruction = set_judgment_ix(&program_id, &wallet_b.pubkey(), &wager_key, 2, 1, Judgment::Push);
+
+    let mut approval_transaction = Transaction::new_with_payer(
+        &[approval_instruction],
+        Some(&payer.pubkey())
+    );
+    approval_transaction.sign(&[&payer, &wallet_b], recent_blockhash);
+    let result = banks_client.process_transaction(approval_transaction).await;
+    assert_eq!(wager_error(result), Some(WagerError::AlreadyResolved));
+
     //
     // STEP SIX: Render payouts
     //
 
-    // wallet_a
-    let variant = WagerInstruction::RenderPayouts;
-    let encoded_data = to_vec(&variant).unwrap();
-
-    let payout_instruction = Instruction::new_with_bytes(
-        program_id,
-        &encoded_data,
-        vec![
-            AccountMeta::new(wallet_a.pubkey(), true),
-            AccountMeta::new(wager_account.pubkey(), false),
-            AccountMeta::new(vault_pda, false),
-            AccountMeta::new_readonly(system_program::id(), false),
-        ],
-    );
+    let balance_a = banks_client.get_balance(wallet_a.pubkey()).await.unwrap();
+    let balance_b = banks_client.get_balance(wallet_b.pubkey()).await.unwrap();
+
+    let payout_instruction = render_payouts_ix(&program_id, &payer.pubkey(), &wager_key, &reserved_seats);
 
     // Create and send transaction
     let mut payout_transaction = Transaction::new_with_payer(
-        &[payout_instruction],
-        Some(&wallet_a.pubkey())
+        std::slice::from_ref(&payout_instruction),
+        Some(&payer.pubkey())
     );
-    payout_transaction.sign(&[&wallet_a], recent_blockhash);
+    payout_transaction.sign(&[&payer], recent_blockhash);
     banks_client.process_transaction(payout_transaction).await.unwrap();
 
-    let vault = banks_client
-        .get_account(vault_pda)
+    // wallet_a forecast higher and the wager landed, so wallet_b pays its risk
+    // calc_risk(0.1 SOL, 65, 15) == (0.04 SOL, 0.06 SOL)
+    let risk_b: u64 = 60_000_000;
+    assert_eq!(
+        banks_client.get_balance(wallet_a.pubkey()).await.unwrap(),
+        balance_a + stake_amount + risk_b
+    );
+    assert_eq!(
+        banks_client.get_balance(wallet_b.pubkey()).await.unwrap(),
+        balance_b + stake_amount - risk_b
+    );
+
+    let vault = banks_client.get_account(vault_pda).await.unwrap().unwrap();
+    assert_eq!(vault.lamports, rent.minimum_balance(0));
+    assert_eq!(wager_phase(&mut banks_client, wager_key).await, Phase::Settled);
+
+    // Settlement cannot be repeated
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
         .await
-        .unwrap()
-        .expect("vault should exist");
+        .unwrap();
 
-    println!("Vault: {:?}", &vault);
-    */
+    let mut payout_transaction = Transaction::new_with_payer(
+        &[payout_instruction],
+        Some(&payer.pubkey())
+    );
+    payout_transaction.sign(&[&payer], recent_blockhash);
+    let result = banks_client.process_transaction(payout_transaction).await;
+    assert_eq!(wager_error(result), Some(WagerError::AlreadySettled));
 
     //
     // FINAL STEP: Get accounts and print!
     //
 
     let account = banks_client
-        .get_account(wager_account.pubkey())
+        .get_account(wager_key)
         .await
         .unwrap()
         .expect("account should exist");
@@ -369,26 +411,3 @@ async fn wager_flow() {
 
     print_seat(&seat_data);
 }
-
-
-pub fn print_wager(wager: &Wager) {
-    println!("📋 WAGER INFO:");
-    println!("  Contract:    {}", wager.contract);
-    println!("  Vault:       {}", wager.vault);
-    println!("  Vault Bump:  {}", wager.vault_bump);
-    println!("  Seat Count:  {}", wager.seat_count);
-    println!("  Capacity:    {}", wager.capacity);
-    println!("  Stake:       {} lamports", wager.stake);
-    println!("\n");
-}
-
-pub fn print_seat(seat: &Seat) {
-    println!("👤 SEAT INFO:");
-    println!("  Wager:       {}", seat.wager);
-    println!("  Authority:   {}", seat.authority);
-    println!("  Belief:      {}", seat.belief);
-    println!("  Status:      {:?}", seat.status);
-    println!("  Judgment:    {:?}", seat.judgment);
-    println!("  Last Change: {}", seat.last_change_at);
-    println!("\n");
-}
\ No newline at end of file
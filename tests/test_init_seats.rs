@@ -0,0 +1,270 @@
+mod common;
+
+use common::{Setup, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{
+    create_batched_wager_ix,
+    init_seats_ix,
+    join_wager_ix,
+    deposit_ix,
+    render_batched_payouts_ix,
+    cancel_batched_wager_ix,
+    close_batched_wager_ix,
+};
+use solana_god::state::{Phase, Judgment};
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::BanksClientError;
+
+use solana_sdk::{
+    signature::Signer,
+    signer::keypair::Keypair
+};
+
+const RESERVED: u8 = 10;
+
+// Reserves ten of twelve seats but only creates the first two with the
+// wager. Wallet 0 holds the first seat, wallet 1 is left to join.
+async fn setup() -> (Setup, Pubkey, Vec<Pubkey>) {
+    let mut setup = Setup::new(2).await;
+
+    let mut authorities = vec![setup.wallets[0].pubkey()];
+    authorities.extend((1..RESERVED).map(|_| Pubkey::new_unique()));
+
+    let mut terms = setup.terms();
+    terms.capacity = 12;
+
+    let wager = setup.next_wager().await;
+    let instruction = create_batched_wager_ix(&setup.program_id, &setup.payer(), &wager, &terms, RESERVED, &authorities[..2]);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    (setup, wager, authorities)
+}
+
+async fn init_seats(setup: &mut Setup, wager: &Pubkey, creator: &Keypair, start: u8, authorities: &[Pubkey]) -> Result<(), BanksClientError> {
+    let instruction = init_seats_ix(&setup.program_id, &creator.pubkey(), wager, start, authorities);
+    setup.send(&[instruction], &[creator]).await
+}
+
+async fn deposit(setup: &mut Setup, wager: &Pubkey) -> Result<(), BanksClientError> {
+    let instruction = deposit_ix(&setup.program_id, &setup.wallets[0].pubkey(), wager, 0, STAKE);
+    setup.play(0, &[instruction]).await
+}
+
+#[tokio::test]
+async fn reserved_seats_are_created_in_batches() {
+    let (mut setup, wager, authorities) = setup().await;
+    let creator = setup.context.payer.insecure_clone();
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!((state.seat_count, state.reserved_count), (2, RESERVED));
+
+    // The wager stays in setup until every reserved seat exists
+    let result = deposit(&mut setup, &wager).await;
+    assert_eq!(wager_error(result), Some(WagerError::SeatsNotInitialized));
+
+    // Batches go in order and only the creator fills them
+    let result = init_seats(&mut setup, &wager, &creator, 4, &authorities[4..6]).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidSeat));
+
+    let stranger = Keypair::new();
+    let result = init_seats(&mut setup, &wager, &stranger, 2, &authorities[2..6]).await;
+    assert_eq!(wager_error(result), Some(WagerError::NotWagerCreator));
+
+    init_seats(&mut setup, &wager, &creator, 2, &authorities[2..6]).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.seat_count, 6);
+
+    let mut overflow = authorities[6..].to_vec();
+    overflow.push(Pubkey::new_unique());
+    let result = init_seats(&mut setup, &wager, &creator, 6, &overflow).await;
+    assert_eq!(wager_error(result), Some(WagerError::TooManyReservedSeats));
+
+    init_seats(&mut setup, &wager, &creator, 6, &authorities[6..]).await.unwrap();
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!(state.seat_count, RESERVED);
+    assert_eq!(state.phase, Phase::Created);
+
+    // Setup is over: reserved seats stake and open seats can be joined
+    deposit(&mut setup, &wager).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Funding);
+
+    let result = init_seats(&mut setup, &wager, &creator, RESERVED, &[Pubkey::new_unique()]).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidPhase));
+}
+
+#[tokio::test]
+async fn joins_wait_for_reserved_seats() {
+    let (mut setup, wager, authorities) = setup().await;
+    let creator = setup.context.payer.insecure_clone();
+    let joiner = setup.wallets[1].pubkey();
+
+    let instruction = join_wager_ix(&setup.program_id, &joiner, &wager, 2);
+    let result = setup.play(1, &[instruction]).await;
+    assert_eq!(wager_error(result), Some(WagerError::SeatsNotInitialized));
+
+    init_seats(&mut setup, &wager, &creator, 2, &authorities[2..]).await.unwrap();
+
+    let instruction = join_wager_ix(&setup.program_id, &joiner, &wager, RESERVED);
+    setup.play(1, &[instruction]).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.seat_count, RESERVED + 1);
+}
+
+#[tokio::test]
+async fn authorities_are_unique_across_batches() {
+    let (mut setup, wager, authorities) = setup().await;
+    let creator = setup.context.payer.insecure_clone();
+
+    // Seat 0 was reserved with the wager itself
+    let mut repeated = authorities[2..6].to_vec();
+    repeated[1] = authorities[0];
+    let result = init_seats(&mut setup, &wager, &creator, 2, &repeated).await;
+    assert_eq!(wager_error(result), Some(WagerError::DuplicateSeatAuthority));
+
+    init_seats(&mut setup, &wager, &creator, 2, &authorities[2..6]).await.unwrap();
+
+    // Seat 3 came from the previous InitSeats batch
+    let mut repeated = authorities[6..].to_vec();
+    repeated[2] = authorities[3];
+    let result = init_seats(&mut setup, &wager, &creator, 6, &repeated).await;
+    assert_eq!(wager_error(result), Some(WagerError::DuplicateSeatAuthority));
+
+    init_seats(&mut setup, &wager, &creator, 6, &authorities[6..]).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.seat_count, RESERVED);
+}
+
+// More seats than one RenderPayouts, CancelWager or CloseWager can carry
+const FULL: u8 = 18;
+const BATCH: u8 = 6;
+
+// Reserves every seat of a FULL-seat wager, in batches
+async fn create_full(setup: &mut Setup) -> Pubkey {
+    let terms = setup.terms();
+    let authorities = setup.authorities();
+
+    let wager = setup.next_wager().await;
+    let instruction = create_batched_wager_ix(&setup.program_id, &setup.payer(), &wager, &terms, FULL, &authorities[..BATCH as usize]);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    let creator = setup.context.payer.insecure_clone();
+    for start in (BATCH..FULL).step_by(BATCH as usize) {
+        let batch = &authorities[start as usize..(start + BATCH) as usize];
+        init_seats(setup, &wager, &creator, start, batch).await.unwrap();
+    }
+    wager
+}
+
+// Closes the wager's seats from the last one down, a batch at a time
+async fn close_full(setup: &mut Setup, wager: &Pubkey) {
+    let payer = setup.payer();
+    for start in (0..FULL).step_by(BATCH as usize).rev() {
+        let recipients = vec![payer; BATCH as usize];
+        let instruction = close_batched_wager_ix(&setup.program_id, &payer, wager, &payer, None, start, &recipients);
+        setup.send(&[instruction], &[]).await.unwrap();
+
+        if start > 0 {
+            assert_eq!(setup.wager_state(wager).await.seat_count, start);
+        }
+    }
+    assert!(!setup.exists(wager).await);
+}
+
+#[tokio::test]
+async fn capacity_runs_to_u8_max() {
+    let mut setup = Setup::new(0).await;
+
+    let mut terms = setup.terms();
+    terms.capacity = u8::MAX;
+    let wager = setup.create(&terms, &[]).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.capacity, u8::MAX);
+}
+
+#[tokio::test]
+async fn full_wagers_settle_in_batches() {
+    let mut setup = Setup::new(FULL as usize).await;
+    let wager = create_full(&mut setup).await;
+
+    let beliefs: Vec<u8> = (0..FULL).map(|index| 10 + 4 * index).collect();
+    setup.lock_all(&wager, &beliefs).await;
+    for index in 0..FULL {
+        setup.judge(&wager, index, Judgment::Landed).await.unwrap();
+    }
+
+    let authorities = setup.authorities();
+    let before: u64 = setup.balances(&authorities).await.iter().sum();
+
+    let payer = setup.payer();
+    for start in (0..FULL).step_by(BATCH as usize) {
+        // Batches have to pick up at the cursor
+        let skipped = start + BATCH;
+        if skipped < FULL {
+            let recipients = &authorities[skipped as usize..(skipped + BATCH) as usize];
+            let instruction = render_batched_payouts_ix(&setup.program_id, &payer, &wager, None, skipped, recipients);
+            let result = setup.send(&[instruction], &[]).await;
+            assert_eq!(wager_error(result), Some(WagerError::InvalidSeat));
+        }
+
+        let recipients = &authorities[start as usize..(start + BATCH) as usize];
+        let instruction = render_batched_payouts_ix(&setup.program_id, &payer, &wager, None, start, recipients);
+        setup.send(&[instruction], &[]).await.unwrap();
+
+        let state = setup.wager_state(&wager).await;
+        assert_eq!((state.phase, state.cursor), (Phase::Settled, start + BATCH));
+
+        // Nothing closes while seats are still owed
+        if state.cursor < FULL {
+            let last = FULL - BATCH;
+            let recipients = &authorities[last as usize..];
+            let instruction = close_batched_wager_ix(&setup.program_id, &payer, &wager, &payer, None, last, recipients);
+            let result = setup.send(&[instruction], &[]).await;
+            assert_eq!(wager_error(result), Some(WagerError::PayoutsPending));
+        }
+    }
+
+    // The whole pot went out across the batches
+    let state = setup.wager_state(&wager).await;
+    assert_eq!(state.phase, Phase::Settled);
+    assert_eq!(state.paid, state.pot);
+
+    let after: u64 = setup.balances(&authorities).await.iter().sum();
+    assert_eq!(after - before, FULL as u64 * STAKE);
+
+    let instruction = render_batched_payouts_ix(&setup.program_id, &payer, &wager, None, 0, &authorities[..BATCH as usize]);
+    let result = setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::AlreadySettled));
+
+    close_full(&mut setup, &wager).await;
+}
+
+#[tokio::test]
+async fn full_wagers_cancel_in_batches() {
+    let mut setup = Setup::new(FULL as usize).await;
+    let wager = create_full(&mut setup).await;
+
+    for index in 0..FULL {
+        setup.stake(&wager, index, 50).await;
+    }
+
+    let authorities = setup.authorities();
+    let before = setup.balances(&authorities).await;
+
+    let payer = setup.payer();
+    for start in (0..FULL).step_by(BATCH as usize) {
+        let recipients = &authorities[start as usize..(start + BATCH) as usize];
+        let instruction = cancel_batched_wager_ix(&setup.program_id, &payer, &wager, None, start, recipients);
+        setup.send(&[instruction], &[]).await.unwrap();
+
+        let state = setup.wager_state(&wager).await;
+        assert_eq!((state.phase, state.cursor), (Phase::Cancelled, start + BATCH));
+    }
+
+    // Every seat got its stake back
+    let after = setup.balances(&authorities).await;
+    for (before, after) in before.iter().zip(after) {
+        assert_eq!(after - before, STAKE);
+    }
+
+    close_full(&mut setup, &wager).await;
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{Setup, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{join_wager_ix, deposit_ix, cancel_wager_ix, close_wager_ix, find_seat_address};
+use solana_god::state::{Status, Fallback};
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::BanksClientError;
+
+use solana_sdk::{
+    signature::Signer,
+    signer::keypair::Keypair
+};
+
+// Opens a three-seat wager with only the first seat reserved; the last
+// wallet arbitrates
+async fn setup() -> (Setup, Pubkey) {
+    let mut setup = Setup::new(5).await;
+    let mut terms = setup.terms();
+    terms.capacity = 3;
+    terms.arbiter = Some(setup.wallets[4].pubkey());
+    terms.fallback = Fallback::Arbiter;
+
+    let reserved = setup.authorities()[0];
+    let wager = setup.create(&terms, &[reserved]).await.unwrap();
+    (setup, wager)
+}
+
+async fn join(setup: &mut Setup, wager: &Pubkey, wallet: &Keypair, index: u8) -> Result<(), BanksClientError> {
+    let instruction = join_wager_ix(&setup.program_id, &wallet.pubkey(), wager, index);
+    setup.send(&[instruction], &[wallet]).await
+}
+
+#[tokio::test]
+async fn open_seats_fill_up_to_capacity() {
+    let (mut setup, wager) = setup().await;
+    assert_eq!(setup.wager_state(&wager).await.seat_count, 1);
+
+    // The arbiter cannot take an open seat either
+    let arbiter = setup.wallet(4);
+    let result = join(&mut setup, &wager, &arbiter, 1).await;
+    assert_eq!(wager_error(result), Some(WagerError::ArbiterCannotHoldSeat));
+
+    // Nobody holds two seats, reserved or joined
+    let reserved = setup.wallet(0);
+    let result = join(&mut setup, &wager, &reserved, 1).await;
+    assert_eq!(wager_error(result), Some(WagerError::DuplicateSeatAuthority));
+
+    for index in 1..3u8 {
+        let wallet = setup.wallet(index as usize);
+        join(&mut setup, &wager, &wallet, index).await.unwrap();
+
+        let seat = setup.seat(&wager, index).await.unwrap();
+        assert_eq!(seat.authority, wallet.pubkey());
+        assert_eq!(seat.status, Status::Open);
+
+        let result = join(&mut setup, &wager, &wallet, index + 1).await;
+        let expected = if index < 2 { WagerError::DuplicateSeatAuthority } else { WagerError::WagerFull };
+        assert_eq!(wager_error(result), Some(expected));
+    }
+
+    assert_eq!(setup.wager_state(&wager).await.seat_count, 3);
+
+    let latecomer = setup.wallet(3);
+    let result = join(&mut setup, &wager, &latecomer, 3).await;
+    assert_eq!(wager_error(result), Some(WagerError::WagerFull));
+
+    // Joined seats are staked like reserved ones
+    let wallet = setup.wallets[2].pubkey();
+    let instruction = deposit_ix(&setup.program_id, &wallet, &wager, 2, STAKE);
+    setup.play(2, &[instruction]).await.unwrap();
+    assert_eq!(setup.seat(&wager, 2).await.unwrap().status, Status::Staked);
+}
+
+#[tokio::test]
+async fn joins_close_with_the_join_deadline() {
+    let (mut setup, wager) = setup().await;
+
+    // Only the next seat in line can be created
+    let wallet = setup.wallet(1);
+    let mut instruction = join_wager_ix(&setup.program_id, &wallet.pubkey(), &wager, 1);
+    instruction.accounts[2].pubkey = find_seat_address(&setup.program_id, &wager, 2).0;
+    let result = setup.send(&[instruction], &[&wallet]).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidSeat));
+
+    // Every seat taken so far has to be passed
+    let result = join(&mut setup, &wager, &wallet, 2).await;
+    assert_eq!(wager_error(result), Some(WagerError::SeatCountMismatch));
+
+    setup.warp_to(setup.now + 3_601).await;
+
+    let result = join(&mut setup, &wager, &wallet, 1).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+    assert_eq!(setup.wager_state(&wager).await.seat_count, 1);
+}
+
+#[tokio::test]
+async fn joined_seats_return_rent_to_their_holders() {
+    let (mut setup, wager) = setup().await;
+    let program_id = setup.program_id;
+    let payer = setup.payer();
+
+    for index in 1..3u8 {
+        let wallet = setup.wallet(index as usize);
+        join(&mut setup, &wager, &wallet, index).await.unwrap();
+    }
+
+    let authorities: Vec<Pubkey> = setup.authorities()[..3].to_vec();
+    let instruction = cancel_wager_ix(&program_id, &payer, &wager, &authorities);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    let seats: Vec<Pubkey> = (1..3).map(|index| find_seat_address(&program_id, &wager, index).0).collect();
+    let rent = setup.balances(&seats).await;
+    let balances = setup.balances(&authorities[1..]).await;
+
+    // Joined seats were not paid for by the creator
+    let instruction = close_wager_ix(&program_id, &payer, &wager, &payer, 3, &[payer, payer]);
+    let result = setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::WrongRentRecipient));
+
+    let instruction = close_wager_ix(&program_id, &payer, &wager, &payer, 3, &authorities[1..]);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    for (index, balance) in setup.balances(&authorities[1..]).await.into_iter().enumerate() {
+        assert_eq!(balance, balances[index] + rent[index]);
+    }
+    assert!(!setup.exists(&wager).await);
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use common::{Setup, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{find_seat_address, join_wager_ix, leave_seat_ix, deposit_ix, update_belief_ix};
+use solana_god::state::{Seat, Status, Phase};
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::BanksClientError;
+
+use solana_sdk::signature::Signer;
+
+// Opens a three-seat wager with the first `reserved` of four wallets holding seats
+async fn setup(reserved: usize) -> (Setup, Pubkey) {
+    let mut setup = Setup::new(4).await;
+    let mut terms = setup.terms();
+    terms.capacity = 3;
+
+    let authorities = setup.authorities();
+    let wager = setup.create(&terms, &authorities[..reserved]).await.unwrap();
+    (setup, wager)
+}
+
+async fn leave(setup: &mut Setup, wager: &Pubkey, index: u8, wallet: usize, payer: &Pubkey) -> Result<(), BanksClientError> {
+    let authority = setup.wallets[wallet].pubkey();
+    let seat_count = setup.wager_state(wager).await.seat_count;
+    let instruction = leave_seat_ix(&setup.program_id, &authority, wager, payer, seat_count, index);
+    setup.play(wallet, &[instruction]).await
+}
+
+async fn seat_rent(setup: &mut Setup) -> u64 {
+    setup.context.banks_client.get_rent().await.unwrap().minimum_balance(Seat::SPACE)
+}
+
+#[tokio::test]
+async fn reserved_seat_opens_for_joining() {
+    let (mut setup, wager) = setup(3).await;
+    let creator = setup.context.payer.pubkey();
+    let stranger = setup.wallets[3].pubkey();
+
+    setup.stake(&wager, 0, 65).await;
+
+    // Only the holder of an unstaked seat can give it up
+    let result = leave(&mut setup, &wager, 1, 2, &creator).await;
+    assert_eq!(wager_error(result), Some(WagerError::WrongSeatAuthority));
+
+    let result = leave(&mut setup, &wager, 0, 0, &creator).await;
+    assert_eq!(wager_error(result), Some(WagerError::SeatNotOpen));
+
+    // The creator paid for reserved seats, so their rent goes back there
+    let result = leave(&mut setup, &wager, 1, 1, &stranger).await;
+    assert_eq!(wager_error(result), Some(WagerError::WrongRentRecipient));
+
+    let rent = seat_rent(&mut setup).await;
+    let (_, bump) = find_seat_address(&setup.program_id, &wager, 1);
+    let before = setup.balance(&creator).await;
+    leave(&mut setup, &wager, 1, 1, &creator).await.unwrap();
+
+    // The creator also pays the fee for both signatures
+    let after = setup.balance(&creator).await;
+    assert_eq!(after + 10_000, before + rent);
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!((state.seat_count, state.reserved_count), (2, 2));
+
+    // The third seat moved down and lives at its new PDA
+    let seat = setup.seat(&wager, 1).await.unwrap();
+    let latecomer_seat = setup.wallets[2].pubkey();
+    assert_eq!(seat.authority, latecomer_seat);
+    assert_eq!(seat.bump, bump);
+    assert!(setup.seat(&wager, 2).await.is_none());
+
+    // The freed slot is open to anyone
+    let latecomer = setup.wallets[3].insecure_clone();
+    let instruction = join_wager_ix(&setup.program_id, &latecomer.pubkey(), &wager, 2);
+    setup.send(&[instruction], &[&latecomer]).await.unwrap();
+    assert_eq!(setup.seat(&wager, 2).await.unwrap().authority, latecomer.pubkey());
+
+    let instructions = [
+        deposit_ix(&setup.program_id, &latecomer_seat, &wager, 1, STAKE),
+        update_belief_ix(&setup.program_id, &latecomer_seat, &wager, 3, 1, 65),
+    ];
+    setup.play(2, &instructions).await.unwrap();
+    assert_eq!(setup.seat(&wager, 1).await.unwrap().status, Status::Staked);
+}
+
+#[tokio::test]
+async fn joined_seat_refunds_its_holder_and_resets_locks() {
+    let (mut setup, wager) = setup(2).await;
+    let joiner = setup.wallets[2].insecure_clone();
+
+    let instruction = join_wager_ix(&setup.program_id, &joiner.pubkey(), &wager, 2);
+    setup.send(&[instruction], &[&joiner]).await.unwrap();
+
+    setup.stake(&wager, 0, 65).await;
+    setup.stake(&wager, 1, 65).await;
+
+    setup.lock(&wager, 0).await.unwrap();
+    assert_eq!(setup.seat(&wager, 0).await.unwrap().status, Status::Locked);
+
+    // Joined seats were paid for by their holder
+    let creator = setup.context.payer.pubkey();
+    let result = leave(&mut setup, &wager, 2, 2, &creator).await;
+    assert_eq!(wager_error(result), Some(WagerError::WrongRentRecipient));
+
+    let rent = seat_rent(&mut setup).await;
+    let before = setup.balance(&joiner.pubkey()).await;
+    leave(&mut setup, &wager, 2, 2, &joiner.pubkey()).await.unwrap();
+
+    let after = setup.balance(&joiner.pubkey()).await;
+    assert_eq!(after, before + rent);
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!((state.seat_count, state.reserved_count), (2, 2));
+
+    // Nobody stays locked into terms made with a different table
+    assert_eq!(setup.seat(&wager, 0).await.unwrap().status, Status::Staked);
+}
+
+#[tokio::test]
+async fn unstaked_seat_is_dropped_after_join_deadline() {
+    let (mut setup, wager) = setup(2).await;
+    let stranger = setup.wallets[2].insecure_clone();
+
+    // The stranger takes the last seat and never stakes it
+    let instruction = join_wager_ix(&setup.program_id, &stranger.pubkey(), &wager, 2);
+    setup.send(&[instruction], &[&stranger]).await.unwrap();
+
+    setup.stake(&wager, 0, 65).await;
+    setup.stake(&wager, 1, 35).await;
+    setup.lock(&wager, 0).await.unwrap();
+    setup.lock(&wager, 1).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Funding);
+
+    // While it can still be staked, only its holder gives it up
+    let result = leave(&mut setup, &wager, 2, 0, &stranger.pubkey()).await;
+    assert_eq!(wager_error(result), Some(WagerError::WrongSeatAuthority));
+
+    setup.warp_to(setup.now + 3_601).await;
+
+    // Staked seats are never dropped this way
+    let creator = setup.context.payer.pubkey();
+    let result = leave(&mut setup, &wager, 1, 0, &creator).await;
+    assert_eq!(wager_error(result), Some(WagerError::SeatNotOpen));
+
+    let rent = seat_rent(&mut setup).await;
+    let before = setup.balance(&stranger.pubkey()).await;
+    leave(&mut setup, &wager, 2, 0, &stranger.pubkey()).await.unwrap();
+    assert_eq!(setup.balance(&stranger.pubkey()).await, before + rent);
+
+    // The remaining seats lock against each other
+    setup.lock(&wager, 0).await.unwrap();
+    setup.lock(&wager, 1).await.unwrap();
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Locked);
+}
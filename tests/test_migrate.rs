@@ -0,0 +1,221 @@
+mod common;
+
+use borsh::to_vec;
+
+use common::{send, wager_error};
+
+use solana_god::process_instruction;
+use solana_god::error::WagerError;
+use solana_god::sdk::{
+    find_seat_address,
+    find_vault_address,
+    migrate_account_ix,
+    migrate_wager_ix,
+    cancel_wager_ix,
+    close_wager_ix,
+};
+use solana_god::state::{Wager, WagerV0, Seat, SeatV0, Phase, Status, Judgment};
+
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+};
+
+use solana_sdk_ids::system_program;
+
+use solana_program_test::*;
+
+use solana_sdk::{
+    account::Account,
+    signature::Signer,
+    transaction::Transaction,
+    signer::keypair::Keypair
+};
+
+const STAKE: u64 = 100_000_000; // 0.1 SOL
+
+// Program-owned account holding `data`, funded for exactly its length
+fn legacy_account(program_id: &Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn migrates_v0_accounts() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "solana_god",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let wager_key = Pubkey::new_unique();
+    let (first_seat_key, _) = find_seat_address(&program_id, &wager_key, 0);
+    let (seat_key, seat_bump) = find_seat_address(&program_id, &wager_key, 1);
+    let contract = Pubkey::new_unique();
+    let (vault, vault_bump) = find_vault_address(&program_id, &wager_key);
+    let authority = Pubkey::new_unique();
+    let first_authority = Pubkey::new_unique();
+
+    // v0 layouts as first deployed, before any of the later wager fields
+    let wager_v0 = to_vec(&(contract, vault, vault_bump, 2u8, 2u8, 100_000_000u64)).unwrap();
+    let seat_v0 = to_vec(&(wager_key, authority, 65u8, Status::Locked, Judgment::Landed, 1_700_000_000i64)).unwrap();
+    assert_eq!(wager_v0.len(), WagerV0::SPACE);
+    assert_eq!(seat_v0.len(), SeatV0::SPACE);
+
+    // Both are 75 bytes, so length alone cannot tell them apart
+    assert_eq!(WagerV0::SPACE, SeatV0::SPACE);
+
+    program_test.add_account(wager_key, legacy_account(&program_id, wager_v0));
+    program_test.add_account(seat_key, legacy_account(&program_id, seat_v0));
+
+    let first_seat_v0 = to_vec(&(wager_key, first_authority, 35u8, Status::Staked, Judgment::Landed, 1_700_000_000i64)).unwrap();
+    program_test.add_account(first_seat_key, legacy_account(&program_id, first_seat_v0));
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            migrate_wager_ix(&program_id, &payer.pubkey(), &wager_key),
+            migrate_account_ix(&program_id, &payer.pubkey(), &seat_key),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let wager_account = banks_client.get_account(wager_key).await.unwrap().unwrap();
+    assert_eq!(wager_account.data.len(), Wager::SPACE);
+    assert!(wager_account.lamports >= rent.minimum_balance(Wager::SPACE));
+
+    let wager = Wager::load(&wager_account.data).unwrap();
+    assert_eq!(wager.version, Wager::VERSION);
+    assert_eq!(wager.phase, Phase::Funding);
+    assert_eq!(wager.contract, contract);
+    assert_eq!(wager.vault, vault);
+    assert_eq!(wager.vault_bump, vault_bump);
+    assert_eq!((wager.seat_count, wager.capacity), (2, 2));
+    assert_eq!(wager.stake, 100_000_000);
+    assert_eq!(wager.outcome, Judgment::Pending);
+
+    // The first seat's holder takes the rent back, not whoever paid to migrate
+    assert_eq!(wager.creator, first_authority);
+
+    let seat_account = banks_client.get_account(seat_key).await.unwrap().unwrap();
+    assert_eq!(seat_account.data.len(), Seat::SPACE);
+
+    let seat = Seat::load(&seat_account.data).unwrap();
+    assert_eq!(seat.wager, wager_key);
+    assert_eq!(seat.authority, authority);
+    assert_eq!(seat.bump, seat_bump);
+    assert_eq!(seat.belief, 65);
+    assert_eq!(seat.judgment, Judgment::Landed);
+
+    // The wager is funding again, so its seats lock again too
+    assert_eq!(seat.status, Status::Staked);
+    assert_eq!(seat.last_change_at, 1_700_000_000);
+
+    // Discriminators keep one account type from being read as another
+    assert_eq!(
+        Wager::load(&seat_account.data).unwrap_err(),
+        ProgramError::from(WagerError::InvalidAccountType),
+    );
+
+    // Upgraded accounts cannot be migrated twice
+    let recent_blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[migrate_wager_ix(&program_id, &payer.pubkey(), &wager_key)],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(wager_error(result), Some(WagerError::NotLegacyAccount));
+}
+
+#[tokio::test]
+async fn migrated_wager_closes_to_its_first_holder() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "solana_god",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let wager_key = Pubkey::new_unique();
+    let (vault, vault_bump) = find_vault_address(&program_id, &wager_key);
+    let holders = [Keypair::new(), Keypair::new()];
+    let authorities: Vec<Pubkey> = holders.iter().map(|holder| holder.pubkey()).collect();
+
+    // A v0 wager both holders staked into, with their stakes in the vault
+    let wager_v0 = to_vec(&(Pubkey::new_unique(), vault, vault_bump, 2u8, 2u8, STAKE)).unwrap();
+    program_test.add_account(wager_key, legacy_account(&program_id, wager_v0));
+
+    for (index, authority) in authorities.iter().enumerate() {
+        let (seat_key, _) = find_seat_address(&program_id, &wager_key, index as u8);
+        let seat_v0 = to_vec(&(wager_key, *authority, 50u8, Status::Staked, Judgment::Pending, 0i64)).unwrap();
+        program_test.add_account(seat_key, legacy_account(&program_id, seat_v0));
+    }
+
+    program_test.add_account(
+        vault,
+        Account {
+            lamports: Rent::default().minimum_balance(0) + 2 * STAKE,
+            data: Vec::new(),
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        authorities[0],
+        Account {
+            lamports: 1_000_000_000,
+            data: Vec::new(),
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.pubkey();
+
+    let mut instructions = vec![migrate_wager_ix(&program_id, &payer, &wager_key)];
+    for index in 0..2u8 {
+        let (seat_key, _) = find_seat_address(&program_id, &wager_key, index);
+        instructions.push(migrate_account_ix(&program_id, &payer, &seat_key));
+    }
+    send(&mut context, &instructions, &[]).await.unwrap();
+
+    // The first holder cancels, which refunds both stakes
+    let instruction = cancel_wager_ix(&program_id, &authorities[0], &wager_key, &authorities);
+    send(&mut context, &[instruction], &[&holders[0]]).await.unwrap();
+
+    let mut rent = 0;
+    let mut closed = vec![wager_key, vault];
+    for index in 0..2u8 {
+        closed.push(find_seat_address(&program_id, &wager_key, index).0);
+    }
+    for key in &closed {
+        rent += context.banks_client.get_balance(*key).await.unwrap();
+    }
+    let before = context.banks_client.get_balance(authorities[0]).await.unwrap();
+
+    // Anyone can close it, and the rent goes back to the recorded creator
+    let instruction = close_wager_ix(&program_id, &payer, &wager_key, &authorities[0], 2, &[]);
+    send(&mut context, &[instruction], &[]).await.unwrap();
+
+    let after = context.banks_client.get_balance(authorities[0]).await.unwrap();
+    assert_eq!(after, before + rent);
+    for key in &closed {
+        assert!(context.banks_client.get_account(*key).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::{Setup, program_test, preload_attestation, wager_error, STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{find_attestation_address, publish_attestation_ix, resolve_from_oracle_ix, render_payouts_ix};
+use solana_god::state::{Phase, Judgment};
+
+use solana_program::pubkey::Pubkey;
+
+use solana_program_test::{BanksClientError, ProgramTest};
+
+use solana_sdk::{
+    signature::Signer,
+    signer::keypair::Keypair
+};
+
+// Stand-in for an off-chain oracle: a keypair that publishes attestations
+// through the program, or whose attestations are preloaded at genesis
+struct MockOracle {
+    keypair: Keypair,
+}
+
+impl MockOracle {
+    fn new() -> Self {
+        MockOracle { keypair: Keypair::new() }
+    }
+
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn attest(&self, setup: &mut Setup, contract: &Pubkey, outcome: Judgment) -> Result<(), BanksClientError> {
+        let instruction = publish_attestation_ix(&setup.program_id, &self.pubkey(), contract, outcome);
+        setup.send(&[instruction], &[&self.keypair]).await
+    }
+
+    fn preload(&self, program_test: &mut ProgramTest, program_id: &Pubkey, contract: &Pubkey, outcome: Judgment, timestamp: i64) {
+        preload_attestation(program_test, program_id, &self.pubkey(), contract, outcome, timestamp);
+    }
+}
+
+// Opens a two-seat wager on `contract` resolved by `oracle`, if any
+async fn setup(program_test: ProgramTest, program_id: Pubkey, contract: Pubkey, oracle: Option<Pubkey>) -> (Setup, Pubkey) {
+    let mut setup = Setup::start(program_test, program_id, 2).await;
+    let mut terms = setup.terms();
+    terms.contract = contract;
+    terms.oracle = oracle;
+
+    let wager = setup.create_for_all(&terms).await;
+    (setup, wager)
+}
+
+async fn resolve(setup: &mut Setup, wager: &Pubkey, oracle: &Pubkey, contract: &Pubkey) -> Result<(), BanksClientError> {
+    let instruction = resolve_from_oracle_ix(&setup.program_id, &setup.payer(), wager, oracle, contract);
+    setup.send(&[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn oracle_resolves_without_seat_judgments() {
+    let program_id = Pubkey::new_unique();
+    let contract = Pubkey::new_unique();
+    let oracle = MockOracle::new();
+
+    let (mut setup, wager) = setup(program_test(program_id), program_id, contract, Some(oracle.pubkey())).await;
+    setup.fund(&[oracle.pubkey()]).await;
+
+    oracle.attest(&mut setup, &contract, Judgment::Landed).await.unwrap();
+
+    // Published outcomes cannot be rewritten
+    let result = oracle.attest(&mut setup, &contract, Judgment::Missed).await;
+    assert_eq!(wager_error(result), Some(WagerError::AlreadyResolved));
+
+    // The attestation only counts once the terms are locked
+    let result = resolve(&mut setup, &wager, &oracle.pubkey(), &contract).await;
+    assert_eq!(wager_error(result), Some(WagerError::WagerNotLocked));
+
+    setup.lock_all(&wager, &[65, 15]).await;
+    resolve(&mut setup, &wager, &oracle.pubkey(), &contract).await.unwrap();
+
+    let state = setup.wager_state(&wager).await;
+    assert_eq!(state.outcome, Judgment::Landed);
+    assert_eq!(state.phase, Phase::Judging);
+
+    let result = resolve(&mut setup, &wager, &oracle.pubkey(), &contract).await;
+    assert_eq!(wager_error(result), Some(WagerError::AlreadyResolved));
+
+    // Nobody judged, yet the wager pays out: calc_risk(0.1 SOL, 65, 15).1 moves to seat 0
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+
+    let instruction = render_payouts_ix(&program_id, &setup.payer(), &wager, &authorities);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    let risk_b = 60_000_000;
+    assert_eq!(setup.balance(&authorities[0]).await, balances[0] + STAKE + risk_b);
+    assert_eq!(setup.balance(&authorities[1]).await, balances[1] + STAKE - risk_b);
+}
+
+#[tokio::test]
+async fn only_the_named_oracle_counts() {
+    let program_id = Pubkey::new_unique();
+    let contract = Pubkey::new_unique();
+    let oracle = MockOracle::new();
+    let imposter = MockOracle::new();
+
+    let mut program_test = program_test(program_id);
+    imposter.preload(&mut program_test, &program_id, &contract, Judgment::Missed, 0);
+
+    let (mut setup, wager) = setup(program_test, program_id, contract, Some(oracle.pubkey())).await;
+    setup.lock_all(&wager, &[65, 15]).await;
+
+    let result = resolve(&mut setup, &wager, &imposter.pubkey(), &contract).await;
+    assert_eq!(wager_error(result), Some(WagerError::InvalidAttestation));
+    assert_eq!(setup.wager_state(&wager).await.outcome, Judgment::Pending);
+}
+
+#[tokio::test]
+async fn seat_judged_wagers_ignore_oracles() {
+    let program_id = Pubkey::new_unique();
+    let contract = Pubkey::new_unique();
+    let oracle = MockOracle::new();
+
+    let mut program_test = program_test(program_id);
+    oracle.preload(&mut program_test, &program_id, &contract, Judgment::Landed, 0);
+
+    let (mut setup, wager) = setup(program_test, program_id, contract, None).await;
+    setup.lock_all(&wager, &[65, 15]).await;
+
+    let result = resolve(&mut setup, &wager, &oracle.pubkey(), &contract).await;
+    assert_eq!(wager_error(result), Some(WagerError::OracleNotConfigured));
+}
+
+#[tokio::test]
+async fn attestations_after_resolve_deadline_are_rejected() {
+    let program_id = Pubkey::new_unique();
+    let contract = Pubkey::new_unique();
+    let oracle = MockOracle::new();
+
+    let mut program_test = program_test(program_id);
+    oracle.preload(&mut program_test, &program_id, &contract, Judgment::Landed, i64::MAX);
+
+    let (mut setup, wager) = setup(program_test, program_id, contract, Some(oracle.pubkey())).await;
+    setup.lock_all(&wager, &[65, 15]).await;
+
+    let result = resolve(&mut setup, &wager, &oracle.pubkey(), &contract).await;
+    assert_eq!(wager_error(result), Some(WagerError::DeadlinePassed));
+}
+
+#[tokio::test]
+async fn prefunded_attestation_can_still_be_published() {
+    let program_id = Pubkey::new_unique();
+    let contract = Pubkey::new_unique();
+    let oracle = MockOracle::new();
+
+    let (mut setup, _) = setup(program_test(program_id), program_id, contract, Some(oracle.pubkey())).await;
+    let (attestation, _) = find_attestation_address(&program_id, &oracle.pubkey(), &contract);
+    setup.fund(&[oracle.pubkey(), attestation]).await;
+
+    oracle.attest(&mut setup, &contract, Judgment::Landed).await.unwrap();
+
+    let account = setup.context.banks_client.get_account(attestation).await.unwrap().unwrap();
+    assert_eq!(account.owner, program_id);
+}
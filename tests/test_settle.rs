@@ -0,0 +1,42 @@
+mod common;
+
+use common::{Setup, STAKE};
+
+use solana_god::sdk::{find_vault_address, render_payouts_ix};
+use solana_god::state::{Phase, Judgment};
+
+#[tokio::test]
+async fn three_seats_settle_pairwise() {
+    let mut setup = Setup::new(3).await;
+    let terms = setup.terms();
+    let wager = setup.create_for_all(&terms).await;
+
+    setup.lock_all(&wager, &[80, 50, 20]).await;
+    for index in 0..3 {
+        setup.judge(&wager, index, Judgment::Landed).await.unwrap();
+    }
+
+    let authorities = setup.authorities();
+    let balances = setup.balances(&authorities).await;
+    let (vault, _) = find_vault_address(&setup.program_id, &wager);
+    let vault_balance = setup.balance(&vault).await;
+
+    let instruction = render_payouts_ix(&setup.program_id, &setup.payer(), &wager, &authorities);
+    setup.send(&[instruction], &[]).await.unwrap();
+
+    // Each pairing stakes half a stake: seat 0 takes calc_risk(0.05 SOL, 80, 50).1
+    // and calc_risk(0.05 SOL, 80, 20).1, seat 1 takes calc_risk(0.05 SOL, 50, 20).1
+    let payouts = [
+        STAKE + 10_500_000 + 30_000_000,
+        STAKE - 10_500_000 + 19_500_000,
+        STAKE - 30_000_000 - 19_500_000,
+    ];
+
+    for ((authority, balance), payout) in authorities.iter().zip(&balances).zip(payouts) {
+        assert_eq!(setup.balance(authority).await, balance + payout);
+    }
+
+    // The whole pot leaves the vault
+    assert_eq!(setup.balance(&vault).await, vault_balance - 3 * STAKE);
+    assert_eq!(setup.wager_state(&wager).await.phase, Phase::Settled);
+}
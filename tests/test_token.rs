@@ -0,0 +1,100 @@
+mod common;
+
+use common::{TokenSetup, wager_error, MINTED, TOKEN_STAKE as STAKE};
+
+use solana_god::error::WagerError;
+use solana_god::sdk::{
+    find_vault_address,
+    render_token_payouts_ix,
+    cancel_token_wager_ix,
+    close_token_wager_ix,
+};
+use solana_god::state::Judgment;
+
+use solana_sdk::signature::Signer;
+
+// A two-seat wager staked in a legacy SPL Token mint
+async fn setup() -> TokenSetup {
+    TokenSetup::new(spl_token::ID, &[], |_, _| Vec::new()).await
+}
+
+#[tokio::test]
+async fn token_wager_flow() {
+    let mut token = setup().await;
+    let program_id = token.setup.program_id;
+    let wager = token.wager;
+    let mint = token.mint;
+
+    // The vault is a token account of the wager mint, controlled by its own PDA
+    let (vault_pda, _) = find_vault_address(&program_id, &wager);
+    let vault = token.token_account(vault_pda).await;
+    assert_eq!(vault.mint, mint.mint);
+    assert_eq!(vault.owner, vault_pda);
+
+    token.deposit(0, 65).await;
+    token.deposit(1, 15).await;
+    assert_eq!(token.token_account(vault_pda).await.amount, 2 * STAKE);
+
+    for index in 0..2u8 {
+        token.setup.lock(&wager, index).await.unwrap();
+    }
+
+    for index in 0..2u8 {
+        token.setup.judge(&wager, index, Judgment::Landed).await.unwrap();
+    }
+
+    // Recipients must be token accounts of the matching seat authority
+    let payer = token.setup.payer();
+    let swapped = [token.token_accounts[1], token.token_accounts[0]];
+    let instruction = render_token_payouts_ix(&program_id, &payer, &wager, &mint, &swapped);
+    let result = token.setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::WrongSeatAuthority));
+
+    let recipients = token.token_accounts.clone();
+    let instruction = render_token_payouts_ix(&program_id, &payer, &wager, &mint, &recipients);
+    token.setup.send(&[instruction], &[]).await.unwrap();
+
+    // calc_risk(1 token, 65, 15) == (0.4 token, 0.6 token)
+    let risk_b = 600_000;
+    assert_eq!(token.token_account(recipients[0]).await.amount, MINTED + risk_b);
+    assert_eq!(token.token_account(recipients[1]).await.amount, MINTED - risk_b);
+    assert_eq!(token.token_account(vault_pda).await.amount, 0);
+
+    // Anyone can still send tokens to the vault after it paid out
+    let wallet = token.setup.wallets[0].pubkey();
+    let instruction = spl_token::instruction::transfer(&spl_token::ID, &recipients[0], &vault_pda, &wallet, &[], 1).unwrap();
+    token.setup.play(0, &[instruction]).await.unwrap();
+
+    // Leftovers only go to a token account of the creator
+    let instruction = close_token_wager_ix(&program_id, &payer, &wager, &payer, &mint, &recipients[0], 2, &[]);
+    let result = token.setup.send(&[instruction], &[]).await;
+    assert_eq!(wager_error(result), Some(WagerError::NotWagerCreator));
+
+    // Closing sweeps them to the creator, then closes the empty token vault
+    let creator_account = token.creator_account;
+    let instruction = close_token_wager_ix(&program_id, &payer, &wager, &payer, &mint, &creator_account, 2, &[]);
+    token.setup.send(&[instruction], &[]).await.unwrap();
+
+    assert_eq!(token.token_account(creator_account).await.amount, MINTED + 1);
+    assert!(!token.setup.exists(&vault_pda).await);
+    assert!(!token.setup.exists(&wager).await);
+}
+
+#[tokio::test]
+async fn token_wager_cancel_refunds_tokens() {
+    let mut token = setup().await;
+    let program_id = token.setup.program_id;
+    let wager = token.wager;
+    let mint = token.mint;
+
+    token.deposit(0, 50).await;
+    assert_eq!(token.token_account(token.token_accounts[0]).await.amount, MINTED - STAKE);
+
+    let payer = token.setup.payer();
+    let recipients = token.token_accounts.clone();
+    let instruction = cancel_token_wager_ix(&program_id, &payer, &wager, &mint, &recipients);
+    token.setup.send(&[instruction], &[]).await.unwrap();
+
+    assert_eq!(token.token_account(recipients[0]).await.amount, MINTED);
+    assert_eq!(token.token_account(recipients[1]).await.amount, MINTED);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{TokenSetup, MINTED, TOKEN_STAKE as STAKE};
+
+use solana_god::sdk::{
+    find_vault_address,
+    render_token_payouts_ix,
+    cancel_token_wager_ix,
+    close_token_wager_ix,
+};
+use solana_god::state::Judgment;
+
+use spl_token_2022::extension::{
+    transfer_fee::{instruction::initialize_transfer_fee_config, TransferFeeConfig},
+    BaseStateWithExtensions,
+    ExtensionType,
+    StateWithExtensions,
+};
+use spl_token_2022::state::Mint;
+
+const FEE_BASIS_POINTS: u16 = 100; // 1% of every transfer is withheld
+
+// A two-seat wager staked in a Token-2022 mint with a transfer fee
+async fn setup() -> TokenSetup {
+    TokenSetup::new(spl_token_2022::ID, &[ExtensionType::TransferFeeConfig], |mint, authority| {
+        vec![
+            initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                mint,
+                Some(authority),
+                Some(authority),
+                FEE_BASIS_POINTS,
+                u64::MAX,
+            ).unwrap(),
+        ]
+    })
+    .await
+}
+
+// Amount that arrives when `amount` is sent through the 1% fee
+fn after_fee(amount: u64) -> u64 {
+    amount - (amount * FEE_BASIS_POINTS as u64).div_ceil(10_000)
+}
+
+#[tokio::test]
+async fn token_2022_wager_records_received_stakes() {
+    let mut token = setup().await;
+    let program_id = token.setup.program_id;
+    let wager = token.wager;
+    let mint = token.mint;
+
+    // The vault carries the account extension the fee-bearing mint requires
+    let (vault_pda, _) = find_vault_address(&program_id, &wager);
+    let vault_account = token.setup.context.banks_client.get_account(vault_pda).await.unwrap().unwrap();
+    assert_eq!(vault_account.owner, spl_token_2022::ID);
+
+    token.deposit(0, 65).await;
+    token.deposit(1, 15).await;
+
+    // The pot is what landed in the vault, not two full stakes
+    let received = after_fee(STAKE);
+    assert_eq!(received, 990_000);
+    assert_eq!(token.token_account(vault_pda).await.amount, 2 * received);
+    assert_eq!(token.withheld(vault_pda).await, 2 * (STAKE - received));
+    assert_eq!(token.pot().await, 2 * received);
+
+    for index in 0..2u8 {
+        token.setup.lock(&wager, index).await.unwrap();
+    }
+
+    for index in 0..2u8 {
+        token.setup.judge(&wager, index, Judgment::Landed).await.unwrap();
+    }
+
+    let payer = token.setup.payer();
+    let recipients = token.token_accounts.clone();
+    let instruction = render_token_payouts_ix(&program_id, &payer, &wager, &mint, &recipients);
+    token.setup.send(&[instruction], &[]).await.unwrap();
+
+    // calc_risk(1 token, 65, 15) == (0.4 token, 0.6 token), scaled to the 99% pot
+    let (payout_a, payout_b) = (1_584_000, 396_000);
+    assert_eq!(payout_a + payout_b, 2 * received);
+
+    assert_eq!(token.token_account(recipients[0]).await.amount, MINTED - STAKE + after_fee(payout_a));
+    assert_eq!(token.token_account(recipients[1]).await.amount, MINTED - STAKE + after_fee(payout_b));
+    assert_eq!(token.token_account(vault_pda).await.amount, 0);
+
+    // Closing harvests the fees withheld in the vault into the mint first
+    let creator_account = token.creator_account;
+    let instruction = close_token_wager_ix(&program_id, &payer, &wager, &payer, &mint, &creator_account, 2, &[]);
+    token.setup.send(&[instruction], &[]).await.unwrap();
+
+    assert!(!token.setup.exists(&vault_pda).await);
+    assert!(!token.setup.exists(&wager).await);
+
+    let mint_data = token.data(mint.mint).await;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data).unwrap();
+    let withheld = mint_state.get_extension::<TransferFeeConfig>().unwrap().withheld_amount;
+    assert_eq!(u64::from(withheld), 2 * (STAKE - received));
+}
+
+#[tokio::test]
+async fn token_2022_cancel_refunds_what_arrived() {
+    let mut token = setup().await;
+    let program_id = token.setup.program_id;
+    let wager = token.wager;
+    let mint = token.mint;
+
+    token.deposit(0, 50).await;
+    assert_eq!(token.pot().await, after_fee(STAKE));
+
+    let payer = token.setup.payer();
+    let recipients = token.token_accounts.clone();
+    let instruction = cancel_token_wager_ix(&program_id, &payer, &wager, &mint, &recipients);
+    token.setup.send(&[instruction], &[]).await.unwrap();
+
+    // The refund can only be what the vault received, less the fee on the way out
+    assert_eq!(token.token_account(recipients[0]).await.amount, MINTED - STAKE + after_fee(after_fee(STAKE)));
+    assert_eq!(token.token_account(recipients[1]).await.amount, MINTED);
+
+    let (vault_pda, _) = find_vault_address(&program_id, &wager);
+    assert_eq!(token.token_account(vault_pda).await.amount, 0);
+}
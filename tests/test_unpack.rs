@@ -0,0 +1,210 @@
+use borsh::to_vec;
+
+use proptest::prelude::*;
+
+use solana_god::instruction::WagerInstruction;
+use solana_god::state::{Wager, Phase, Fallback, Judgment};
+
+use solana_program::pubkey::Pubkey;
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn judgment() -> impl Strategy<Value = Judgment> {
+    prop_oneof![
+        Just(Judgment::Pending),
+        Just(Judgment::Landed),
+        Just(Judgment::Missed),
+        Just(Judgment::Push),
+    ]
+}
+
+fn phase() -> impl Strategy<Value = Phase> {
+    prop_oneof![
+        Just(Phase::Created),
+        Just(Phase::Funding),
+        Just(Phase::Locked),
+        Just(Phase::Judging),
+        Just(Phase::Settled),
+        Just(Phase::Cancelled),
+        Just(Phase::Disputed),
+    ]
+}
+
+fn fallback() -> impl Strategy<Value = Fallback> {
+    prop_oneof![
+        Just(Fallback::Push),
+        Just(Fallback::Arbiter),
+        Just(Fallback::Oracle),
+    ]
+}
+
+fn wager() -> impl Strategy<Value = Wager> {
+    (
+        (any::<[u8; 8]>(), any::<u8>(), phase(), pubkey(), pubkey(), pubkey(), pubkey()),
+        (pubkey(), pubkey(), any::<u8>(), any::<u8>(), any::<u8>(), any::<u8>(), any::<u64>(), judgment()),
+        (any::<u64>(), any::<i64>(), any::<i64>(), any::<i64>()),
+        (fallback(), any::<i64>(), any::<i64>()),
+        (any::<u8>(), any::<u8>(), any::<u64>(), any::<u8>(), any::<u32>(), any::<u32>()),
+    )
+        .prop_map(|(
+            (discriminator, version, phase, creator, mint, oracle, arbiter),
+            (contract, vault, vault_bump, seat_count, reserved_count, capacity, stake, outcome),
+            (pot, join_deadline, lock_deadline, resolve_deadline),
+            (fallback, dispute_window, disputed_until),
+            (staked_count, locked_count, paid, cursor, belief_squares, doubt_squares),
+        )| {
+            Wager {
+                discriminator,
+                version,
+                phase,
+                creator,
+                contract,
+                mint,
+                oracle,
+                arbiter,
+                vault,
+                vault_bump,
+                seat_count,
+                reserved_count,
+                staked_count,
+                locked_count,
+                capacity,
+                stake,
+                pot,
+                paid,
+                cursor,
+                join_deadline,
+                lock_deadline,
+                resolve_deadline,
+                fallback,
+                dispute_window,
+                disputed_until,
+                outcome,
+                belief_squares,
+                doubt_squares,
+            }
+        })
+}
+
+fn instruction() -> impl Strategy<Value = WagerInstruction> {
+    prop_oneof![
+        wager().prop_flat_map(|wager| {
+            let capacity = wager.capacity as usize;
+            (Just(wager), prop::collection::vec(pubkey(), 0..=capacity.min(16)))
+        })
+        .prop_map(|(wager, reserved_seats)| WagerInstruction::CreateWager { wager, reserved_seats }),
+        (any::<u8>(), any::<u64>())
+            .prop_map(|(index, amount)| WagerInstruction::ProcessDeposit { index, amount }),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(index, belief)| WagerInstruction::UpdateBelief { index, belief }),
+        any::<u8>().prop_map(|index| WagerInstruction::LockStatus { index }),
+        (any::<u8>(), judgment())
+            .prop_map(|(index, judgment)| WagerInstruction::SetJudgment { index, judgment }),
+        Just(WagerInstruction::RenderPayouts),
+        Just(WagerInstruction::MigrateAccount),
+        Just(WagerInstruction::CancelWager),
+        Just(WagerInstruction::CloseWager),
+        (pubkey(), judgment())
+            .prop_map(|(contract, outcome)| WagerInstruction::PublishAttestation { contract, outcome }),
+        Just(WagerInstruction::ResolveFromOracle),
+        judgment().prop_map(|judgment| WagerInstruction::ArbiterJudgment { judgment }),
+        Just(WagerInstruction::EscalateDispute),
+        Just(WagerInstruction::JoinWager),
+        (any::<u8>(), any::<u8>())
+            .prop_map(|(start, count)| WagerInstruction::InitSeats { start, count }),
+        any::<u8>().prop_map(|index| WagerInstruction::LeaveSeat { index }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn unpack_never_panics(input in prop::collection::vec(any::<u8>(), 0..512)) {
+        let _ = WagerInstruction::unpack(&input);
+    }
+
+    #[test]
+    fn unpack_never_panics_on_known_variants(
+        variant in 0u8..16,
+        rest in prop::collection::vec(any::<u8>(), 0..512),
+    ) {
+        let mut input = vec![variant];
+        input.extend(rest);
+        let _ = WagerInstruction::unpack(&input);
+    }
+
+    #[test]
+    fn unpack_round_trips(instruction in instruction()) {
+        let encoded = to_vec(&instruction).unwrap();
+        prop_assert_eq!(WagerInstruction::unpack(&encoded).unwrap(), instruction);
+    }
+
+    #[test]
+    fn unpack_rejects_trailing_bytes(
+        instruction in instruction(),
+        trailing in prop::collection::vec(any::<u8>(), 1..64),
+    ) {
+        let mut encoded = to_vec(&instruction).unwrap();
+        encoded.extend(trailing);
+        prop_assert!(WagerInstruction::unpack(&encoded).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_truncated_input(instruction in instruction()) {
+        let encoded = to_vec(&instruction).unwrap();
+        for len in 0..encoded.len() {
+            prop_assert!(WagerInstruction::unpack(&encoded[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn unpack_caps_reserved_seats_to_capacity(
+        wager in wager(),
+        extra in 1usize..4,
+    ) {
+        let reserved_seats = vec![Pubkey::new_unique(); wager.capacity as usize + extra];
+        let encoded = to_vec(&WagerInstruction::CreateWager { wager, reserved_seats }).unwrap();
+        prop_assert!(WagerInstruction::unpack(&encoded).is_err());
+    }
+}
+
+#[test]
+fn unpack_rejects_oversized_seat_length_prefix() {
+    let wager = Wager {
+        discriminator: Wager::DISCRIMINATOR,
+        version: Wager::VERSION,
+        phase: Phase::Created,
+        creator: Pubkey::new_unique(),
+        contract: Pubkey::new_unique(),
+        mint: Pubkey::default(),
+        oracle: Pubkey::default(),
+        arbiter: Pubkey::default(),
+        vault: Pubkey::new_unique(),
+        vault_bump: 255,
+        seat_count: 0,
+        reserved_count: 0,
+        staked_count: 0,
+        locked_count: 0,
+        capacity: 2,
+        stake: 1,
+        pot: 0,
+        paid: 0,
+        cursor: 0,
+        join_deadline: 1,
+        lock_deadline: 2,
+        resolve_deadline: 3,
+        fallback: Fallback::Push,
+        dispute_window: 0,
+        disputed_until: 0,
+        outcome: Judgment::Pending,
+        belief_squares: 0,
+        doubt_squares: 0,
+    };
+
+    let mut encoded = vec![0];
+    encoded.extend(to_vec(&wager).unwrap());
+    encoded.extend(u32::MAX.to_le_bytes());
+
+    assert!(WagerInstruction::unpack(&encoded).is_err());
+}